@@ -0,0 +1,208 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use battleship_core::{GameRules, HitType, Position, Seat};
+
+const GAS: &str = "300000000000000";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PlayerView {
+    pub id: String,
+    pub board: [u32; 8],
+    pub shot_x: u32,
+    pub shot_y: u32,
+}
+
+// The parts of the contract's GameState the CLI needs.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GameView {
+    pub next_turn: u32,
+    pub rules: GameRules,
+    pub p1: PlayerView,
+    pub p2: PlayerView,
+    pub last_hit: u8,
+    pub sunk_what: u8,
+    pub winner: Option<String>,
+    #[serde(default)]
+    pub opponent: Option<String>,
+}
+
+impl GameView {
+    pub fn player(&self, player: u32) -> &PlayerView {
+        if player == 1 {
+            &self.p1
+        } else {
+            &self.p2
+        }
+    }
+
+    // The shot `player` has to answer on their turn.
+    pub fn incoming_shot(&self, player: u32) -> Position {
+        let other = self.player(3 - player);
+        Position::new(other.shot_x, other.shot_y)
+    }
+
+    // Result of the shot answered by the previous turn.
+    pub fn last_hit(&self) -> HitType {
+        match self.last_hit {
+            0 => HitType::Miss,
+            1 => HitType::Hit,
+            2 => HitType::Sunk(self.sunk_what),
+            _ => HitType::InvalidShot,
+        }
+    }
+}
+
+/// The operations of `BattleshipContract` that a player drives.
+pub trait Backend {
+    fn list_games(&self) -> Result<Vec<String>>;
+
+    fn game_state(&self, name: &str) -> Result<Option<GameView>>;
+
+    fn new_game(&self, name: &str, receipt: &str, opponent: Option<&str>) -> Result<()>;
+
+    fn join_game(&self, name: &str, receipt: &str, shot: &Position) -> Result<()>;
+
+    fn turn(&self, name: &str, receipt: &str, shot: &Position) -> Result<()>;
+
+    fn reveal(&self, name: &str, receipt: &str) -> Result<()>;
+
+    /// The seat our init and turn receipts for `name` must be proven for.
+    fn seat(&self, name: &str) -> Result<Seat>;
+}
+
+/// Talks to the deployed contract: views go straight to the JSON-RPC
+/// endpoint, while calls are signed by `near-cli` with the account's local
+/// credentials.
+pub struct NearBackend {
+    pub rpc_url: String,
+    pub contract_id: Option<String>,
+    pub account_id: Option<String>,
+}
+
+impl NearBackend {
+    fn contract_id(&self) -> Result<&str> {
+        self.contract_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("no contract given, set --contract or BATTLESHIP_CONTRACT"))
+    }
+
+    fn view<T: DeserializeOwned>(&self, method: &str, args: Value) -> Result<T> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": "battleship-cli",
+            "method": "query",
+            "params": {
+                "request_type": "call_function",
+                "finality": "final",
+                "account_id": self.contract_id()?,
+                "method_name": method,
+                "args_base64": base64::encode(args.to_string()),
+            },
+        });
+        let response: Value = ureq::post(&self.rpc_url)
+            .send_json(request)
+            .with_context(|| format!("view {}", method))?
+            .into_json()?;
+        if let Some(err) = response.get("error") {
+            bail!("view {}: {}", method, err);
+        }
+        let result = &response["result"];
+        if let Some(err) = result.get("error") {
+            bail!("view {}: {}", method, err);
+        }
+        let bytes: Vec<u8> = serde_json::from_value(result["result"].clone())
+            .with_context(|| format!("view {}: unexpected response", method))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn call(&self, method: &str, args: Value) -> Result<()> {
+        let account_id = self
+            .account_id
+            .as_ref()
+            .ok_or_else(|| anyhow!("an account is required to call {}", method))?;
+        let output = Command::new("near")
+            .args(["call", self.contract_id()?, method, &args.to_string()])
+            .args(["--accountId", account_id, "--gas", GAS])
+            .output()
+            .context("failed to run near-cli")?;
+        if !output.status.success() {
+            // Contract panics carry an ERR_* code in the message.
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            bail!("{} failed: {}{}", method, stderr.trim(), stdout.trim());
+        }
+        Ok(())
+    }
+}
+
+impl Backend for NearBackend {
+    fn list_games(&self) -> Result<Vec<String>> {
+        self.view("list_games", json!({}))
+    }
+
+    fn game_state(&self, name: &str) -> Result<Option<GameView>> {
+        self.view("game_state", json!({ "name": name }))
+    }
+
+    fn new_game(&self, name: &str, receipt: &str, opponent: Option<&str>) -> Result<()> {
+        self.call(
+            "new_game",
+            json!({ "name": name, "receipt_str": receipt, "opponent": opponent }),
+        )
+    }
+
+    fn join_game(&self, name: &str, receipt: &str, shot: &Position) -> Result<()> {
+        self.call(
+            "join_game",
+            json!({
+                "name": name,
+                "receipt_str": receipt,
+                "shot_x": shot.x,
+                "shot_y": shot.y,
+            }),
+        )
+    }
+
+    fn turn(&self, name: &str, receipt: &str, shot: &Position) -> Result<()> {
+        self.call(
+            "turn",
+            json!({
+                "name": name,
+                "receipt_str": receipt,
+                "shot_x": shot.x,
+                "shot_y": shot.y,
+            }),
+        )
+    }
+
+    fn reveal(&self, name: &str, receipt: &str) -> Result<()> {
+        self.call("reveal", json!({ "name": name, "receipt_str": receipt }))
+    }
+
+    // The contract checks receipts against the account that signs the call
+    fn seat(&self, name: &str) -> Result<Seat> {
+        let account_id = self
+            .account_id
+            .as_ref()
+            .ok_or_else(|| anyhow!("an account is required to play {}", name))?;
+        Ok(Seat::new(name, account_id))
+    }
+}
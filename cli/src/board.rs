@@ -0,0 +1,97 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use battleship_core::{GameRules, GameState, HitType, Position, ShipDirection};
+
+const WATER: char = '.';
+const MISS: char = 'o';
+const HIT: char = 'X';
+const SUNK: char = '#';
+const PENDING: char = '?';
+const INVALID: char = '!';
+
+type Cells = Vec<Vec<char>>;
+
+fn empty_cells(rules: &GameRules) -> Cells {
+    vec![vec![WATER; rules.width as usize]; rules.height as usize]
+}
+
+fn render(cells: &Cells) -> String {
+    let mut out = String::from("   ");
+    for x in 0..cells[0].len() {
+        write!(out, " {}", x).unwrap();
+    }
+    out.push('\n');
+    for (y, row) in cells.iter().enumerate() {
+        write!(out, "{:>2} ", y).unwrap();
+        for cell in row {
+            write!(out, " {}", cell).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Our own fleet: ships are lettered in fleet order, damaged sections are
+// marked with X and the opponent's misses with o.
+pub fn render_fleet(state: &GameState) -> String {
+    let mut cells = empty_cells(&state.rules);
+    for y in 0..state.rules.height {
+        for x in 0..state.rules.width {
+            if state.shots.contains(&Position::new(x, y)) {
+                cells[y as usize][x as usize] = MISS;
+            }
+        }
+    }
+    for (i, ship) in state.ships.iter().enumerate() {
+        let letter = (b'A' + i as u8) as char;
+        for j in 0..state.rules.span(i) {
+            let (x, y) = match ship.dir {
+                ShipDirection::Horizontal => (ship.pos.x as usize + j, ship.pos.y as usize),
+                ShipDirection::Vertical => (ship.pos.x as usize, ship.pos.y as usize + j),
+            };
+            cells[y][x] = if ship.hit_mask & (1 << j) != 0 {
+                HIT
+            } else {
+                letter
+            };
+        }
+    }
+    render(&cells)
+}
+
+// Our shots at the opponent, with their outcome once it is known.
+pub fn render_targets(rules: &GameRules, shots: &[(Position, Option<HitType>)]) -> String {
+    let mut cells = empty_cells(rules);
+    for (pos, hit) in shots {
+        // Shots off the board were answered as invalid and have no cell
+        let cell = match cells
+            .get_mut(pos.y as usize)
+            .and_then(|row| row.get_mut(pos.x as usize))
+        {
+            Some(cell) => cell,
+            None => continue,
+        };
+        *cell = match hit {
+            None => PENDING,
+            Some(HitType::Miss) => MISS,
+            Some(HitType::Hit) => HIT,
+            Some(HitType::Sunk(_)) => SUNK,
+            Some(HitType::InvalidShot) => INVALID,
+        };
+    }
+    render(&cells)
+}
@@ -0,0 +1,124 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Playing directly against another player over TCP, with no contract in
+//! between. Nothing is saved: the game lasts as long as the connection.
+
+use std::{
+    io::{self, BufRead, Write},
+    sync::Arc,
+};
+
+use anyhow::{bail, Result};
+
+use battleship_core::{GameRules, HitType, Position, Seat};
+use battleship_p2p::{Battleship, Error, Message, TcpTransport, Transport};
+
+use crate::{board, fleet, prover};
+
+fn setup(seat: Seat, rules: GameRules, fleet: Option<String>) -> Result<Battleship> {
+    let ships = match fleet {
+        Some(fleet) => fleet::parse_fleet(&fleet)?,
+        None => fleet::random_fleet(&rules),
+    };
+    let state = fleet::new_game_state(rules, ships)?;
+    Ok(Battleship::new(Arc::new(prover::load()?), seat, state))
+}
+
+pub fn host(addr: &str, rules: GameRules, fleet: Option<String>) -> Result<()> {
+    // A fresh name per game, so receipts from this game are no good in any other
+    let game = format!("{:016x}", rand::random::<u64>());
+    let mut session = setup(Seat::new(&game, "host"), rules, fleet)?;
+    println!("Waiting for an opponent on {}...", addr);
+    let mut transport = TcpTransport::listen(addr)?;
+    println!("Proving fleet...");
+    transport.send(&session.init()?)?;
+    session.on_init_msg(&transport.recv()?)?;
+    play(session, transport, true)
+}
+
+pub fn connect(addr: &str, fleet: Option<String>) -> Result<()> {
+    let mut transport = TcpTransport::connect(addr)?;
+    // The host's init message names the game and the rules to place our fleet under
+    let init = transport.recv()?;
+    let commit = Battleship::peer_init(&init)?;
+    // The host names the game, so make our seat unique ourselves: cheat
+    // evidence pairs two receipts from one seat, which must never span games
+    let player = format!("guest-{:016x}", rand::random::<u64>());
+    let seat = Seat::new(&commit.seat.game, &player);
+    let mut session = setup(seat, commit.rules, fleet)?;
+    session.on_init_msg(&init)?;
+    println!("Proving fleet...");
+    transport.send(&session.init()?)?;
+    play(session, transport, false)
+}
+
+fn play(mut session: Battleship, mut transport: TcpTransport, mut our_turn: bool) -> Result<()> {
+    let mut shots: Vec<(Position, Option<HitType>)> = Vec::new();
+    print!("{}", board::render_fleet(session.state()));
+    loop {
+        if our_turn {
+            println!("Our shots:");
+            print!("{}", board::render_targets(&session.state().rules, &shots));
+            let shot = read_shot()?;
+            transport.send(&session.turn(shot.x, shot.y))?;
+            let commit = match session.on_round_msg(&transport.recv()?) {
+                // Print the evidence so it can be handed to whoever arbitrates
+                Err(Error::Cheat(evidence)) => {
+                    println!("{}", serde_json::to_string_pretty(&evidence)?);
+                    bail!("the opponent cheated: {}", evidence);
+                }
+                result => result?,
+            };
+            println!("Fired at {}: {:?}", shot, commit.hit);
+            shots.push((shot, Some(commit.hit)));
+            if commit.fleet_destroyed {
+                println!("Game over, we won.");
+                return Ok(());
+            }
+        } else {
+            println!("Waiting for the opponent's shot...");
+            let msg = transport.recv()?;
+            if let Message::Turn { shot } = &msg {
+                println!("Proving the answer to {}...", shot);
+            }
+            let (result, reply) = session.on_turn_msg(&msg)?;
+            transport.send(&reply)?;
+            println!("Our fleet: {:?}", result.hit);
+            print!("{}", board::render_fleet(session.state()));
+            if result.fleet_destroyed {
+                println!("Game over, our fleet is destroyed.");
+                return Ok(());
+            }
+        }
+        our_turn = !our_turn;
+    }
+}
+
+fn read_shot() -> Result<Position> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("Fire at x,y: ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => bail!("no shot given"),
+        };
+        match fleet::parse_position(&line) {
+            Ok(shot) => return Ok(shot),
+            Err(err) => println!("{}", err),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail, Result};
+use rand::{rngs::OsRng, thread_rng, Rng};
+
+use battleship_core::{GameCheck, GameRules, GameState, Position, Salt, Ship, ShipDirection};
+
+pub fn variant_rules(variant: &str) -> Result<GameRules> {
+    match variant {
+        "standard" => Ok(GameRules::default()),
+        "quick" => Ok(GameRules::new(8, 8, vec![4, 3, 3, 2])),
+        _ => bail!("unknown variant: {}", variant),
+    }
+}
+
+pub fn random_fleet(rules: &GameRules) -> Vec<Ship> {
+    let mut rng = thread_rng();
+    let mut game_check = GameCheck::new();
+    (0..rules.num_ships())
+        .map(|i| loop {
+            let x = rng.gen_range(0..rules.width);
+            let y = rng.gen_range(0..rules.height);
+            let dir = if rng.gen::<bool>() {
+                ShipDirection::Horizontal
+            } else {
+                ShipDirection::Vertical
+            };
+            let ship = Ship::new(x, y, dir);
+            let span = rules.span(i);
+            if ship.check(span, rules) && game_check.check(&ship, span, false) {
+                game_check.commit(&ship, span);
+                break ship;
+            }
+        })
+        .collect()
+}
+
+// Parses a fleet written as space separated `x,y,h` or `x,y,v` entries, one
+// per ship in the order of the rules' spans.
+pub fn parse_fleet(text: &str) -> Result<Vec<Ship>> {
+    text.split_whitespace()
+        .map(|entry| {
+            let parts: Vec<&str> = entry.split(',').collect();
+            let (x, y, dir) = match parts[..] {
+                [x, y, dir] => (x, y, dir),
+                _ => bail!("expected x,y,h or x,y,v but got {:?}", entry),
+            };
+            let dir = match dir {
+                "h" | "H" => ShipDirection::Horizontal,
+                "v" | "V" => ShipDirection::Vertical,
+                _ => bail!("bad direction in {:?}", entry),
+            };
+            Ok(Ship::new(x.parse()?, y.parse()?, dir))
+        })
+        .collect()
+}
+
+pub fn parse_position(text: &str) -> Result<Position> {
+    let (x, y) = text
+        .split_once(',')
+        .ok_or_else(|| anyhow!("expected x,y but got {:?}", text))?;
+    Ok(Position::new(x.trim().parse()?, y.trim().parse()?))
+}
+
+pub fn new_game_state(rules: GameRules, ships: Vec<Ship>) -> Result<GameState> {
+    let salt: Salt = OsRng.gen();
+    let state = GameState::new(rules, ships, salt);
+    state
+        .validate()
+        .map_err(|err| anyhow!("invalid fleet: {}", err))?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let ships = parse_fleet("0,0,h 7,1,v\t2,5,H 5,7,h").unwrap();
+        assert_eq!(ships.len(), 4);
+        assert_eq!(ships[1], Ship::new(7, 1, ShipDirection::Vertical));
+        assert!(new_game_state(variant_rules("quick").unwrap(), ships).is_ok());
+
+        assert!(parse_fleet("0,0").is_err());
+        assert!(parse_fleet("0,0,x").is_err());
+        assert!(parse_fleet("a,0,h").is_err());
+        assert_eq!(parse_position(" 3, 4").unwrap(), Position::new(3, 4));
+        assert!(parse_position("3").is_err());
+    }
+
+    #[test]
+    fn random() {
+        for variant in ["standard", "quick"] {
+            let rules = variant_rules(variant).unwrap();
+            assert!(new_game_state(rules.clone(), random_fleet(&rules)).is_ok());
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod backend;
+mod board;
+mod duel;
+mod fleet;
+mod prover;
+mod store;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand};
+
+use backend::{Backend, GameView, NearBackend};
+use battleship_core::{GameRules, InitParams, RoundParams};
+use battleship_prover::ProverBackend;
+use store::{Pending, SavedGame, Store};
+
+/// Play battleship from a terminal.
+#[derive(Parser)]
+#[clap(version)]
+struct Cli {
+    /// NEAR account that signs moves.
+    #[clap(long, env = "NEAR_ACCOUNT")]
+    account: Option<String>,
+
+    /// Account the battleship contract is deployed to.
+    #[clap(long, env = "BATTLESHIP_CONTRACT")]
+    contract: Option<String>,
+
+    /// NEAR JSON-RPC endpoint.
+    #[clap(long, env = "NEAR_RPC", default_value = "https://rpc.testnet.near.org")]
+    rpc: String,
+
+    /// Directory holding the secret state of our games.
+    #[clap(long, env = "BATTLESHIP_STATE_DIR", default_value = ".battleship")]
+    state_dir: PathBuf,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List open games.
+    List,
+    /// Create a game and wait for an opponent.
+    New {
+        name: String,
+        /// Board size and fleet: standard or quick.
+        #[clap(long, default_value = "standard")]
+        variant: String,
+        /// Ships as `x,y,h` or `x,y,v` entries; random if omitted.
+        #[clap(long)]
+        fleet: Option<String>,
+        /// Reserve the game for this account; anyone may join if omitted.
+        #[clap(long)]
+        opponent: Option<String>,
+    },
+    /// Join a game and take the first shot.
+    Join {
+        name: String,
+        /// Where to fire, as `x,y`.
+        #[clap(long)]
+        shot: String,
+        /// Ships as `x,y,h` or `x,y,v` entries; random if omitted.
+        #[clap(long)]
+        fleet: Option<String>,
+    },
+    /// Answer the opponent's shot and fire back.
+    Turn {
+        name: String,
+        /// Where to fire, as `x,y`.
+        #[clap(long)]
+        shot: String,
+    },
+    /// Show both boards and whose turn it is.
+    Status { name: String },
+    /// Publish our fleet once the game is over.
+    Reveal { name: String },
+    /// Wait for an opponent to connect and play them directly.
+    Host {
+        /// Address to listen on.
+        #[clap(long, default_value = "0.0.0.0:7777")]
+        addr: String,
+        /// Board size and fleet: standard or quick.
+        #[clap(long, default_value = "standard")]
+        variant: String,
+        /// Ships as `x,y,h` or `x,y,v` entries; random if omitted.
+        #[clap(long)]
+        fleet: Option<String>,
+    },
+    /// Connect to a hosted game and play it directly.
+    Connect {
+        /// Address of the host.
+        addr: String,
+        /// Ships as `x,y,h` or `x,y,v` entries; random if omitted.
+        #[clap(long)]
+        fleet: Option<String>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let backend = NearBackend {
+        rpc_url: cli.rpc,
+        contract_id: cli.contract,
+        account_id: cli.account,
+    };
+    let store = Store::new(&cli.state_dir);
+
+    match cli.command {
+        Command::List => {
+            for name in backend.list_games()? {
+                println!("{}", name);
+            }
+        }
+        Command::New {
+            name,
+            variant,
+            fleet,
+            opponent,
+        } => {
+            let rules = fleet::variant_rules(&variant)?;
+            let mut game = setup(&store, &name, 1, rules, fleet)?;
+            println!("Proving fleet...");
+            let params = InitParams::new(game.state.clone(), backend.seat(&name)?);
+            let receipt = prover::load()?.prove_init(&params)?;
+            store.save(&game)?;
+            backend.new_game(&name, &receipt, opponent.as_deref())?;
+            game.pending = None;
+            store.save(&game)?;
+            print!("{}", board::render_fleet(&game.state));
+            match opponent {
+                Some(opponent) => println!("Created {}, waiting for {}.", name, opponent),
+                None => println!("Created {}, waiting for an opponent.", name),
+            }
+        }
+        Command::Join { name, shot, fleet } => {
+            let view = game_view(&backend, &name)?;
+            if view.next_turn != 0 {
+                bail!("{} already has two players", name);
+            }
+            // Catch what the contract would reject before spending time proving
+            let seat = backend.seat(&name)?;
+            if view.p1.id == seat.player {
+                bail!("{} is our own game", name);
+            }
+            if let Some(opponent) = view.opponent.filter(|id| *id != seat.player) {
+                bail!("{} is reserved for {}", name, opponent);
+            }
+            let shot = fleet::parse_position(&shot)?;
+            let mut game = setup(&store, &name, 2, view.rules, fleet)?;
+            println!("Proving fleet...");
+            let params = InitParams::new(game.state.clone(), seat);
+            let receipt = prover::load()?.prove_init(&params)?;
+            game.shots.push((shot.clone(), None));
+            store.save(&game)?;
+            backend.join_game(&name, &receipt, &shot)?;
+            game.pending = None;
+            store.save(&game)?;
+            print!("{}", board::render_fleet(&game.state));
+            println!("Joined {} and fired at {}.", name, shot);
+        }
+        Command::Turn { name, shot } => {
+            let mut game = store.load(&name)?;
+            let view = game_view(&backend, &name)?;
+            settle(&store, &backend.seat(&name)?.player, &mut game, &view)?;
+            if let Some(winner) = &view.winner {
+                bail!("{} is over, {} won", name, winner);
+            }
+            if view.next_turn != game.player {
+                bail!("not our turn in {}", name);
+            }
+            let shot = fleet::parse_position(&shot)?;
+            record_last_hit(&mut game, &view);
+            let incoming = view.incoming_shot(game.player);
+            println!("Proving the answer to {}...", incoming);
+            let params = RoundParams {
+                state: game.state.clone(),
+                shot: incoming.clone(),
+                seat: backend.seat(&name)?,
+            };
+            let turn = prover::load()?.prove_turn(&params)?;
+            let result = turn.state;
+            let prior = std::mem::replace(&mut game.state, result.state);
+            game.shots.push((shot.clone(), None));
+            game.pending = Some(Pending::Turn {
+                prior,
+                board: prover::board_after(&turn.receipt)?,
+            });
+            store.save(&game)?;
+            backend.turn(&name, &turn.receipt, &shot)?;
+            game.pending = None;
+            store.save(&game)?;
+            println!(
+                "{} at {}: {:?}",
+                view.player(3 - game.player).id,
+                incoming,
+                result.hit
+            );
+            if result.fleet_destroyed {
+                println!("Our fleet is destroyed.");
+            } else {
+                println!("Fired at {}.", shot);
+            }
+        }
+        Command::Status { name } => {
+            let mut game = store.load(&name)?;
+            let view = game_view(&backend, &name)?;
+            settle(&store, &backend.seat(&name)?.player, &mut game, &view)?;
+            if view.next_turn == game.player {
+                record_last_hit(&mut game, &view);
+            }
+            println!("Our fleet:");
+            print!("{}", board::render_fleet(&game.state));
+            println!("Our shots:");
+            print!("{}", board::render_targets(&game.state.rules, &game.shots));
+            match (&view.winner, view.next_turn) {
+                (Some(winner), _) => println!("Game over, {} won.", winner),
+                (None, 0) => println!("Waiting for an opponent."),
+                (None, turn) if turn == game.player => println!("Our turn."),
+                (None, _) => println!("Waiting for the opponent."),
+            }
+        }
+        Command::Reveal { name } => {
+            let game = store.load(&name)?;
+            println!("Proving reveal...");
+            let receipt = prover::load()?.prove_reveal(&game.state)?;
+            backend.reveal(&name, &receipt)?;
+            println!("Revealed our fleet in {}.", name);
+        }
+        Command::Host {
+            addr,
+            variant,
+            fleet,
+        } => {
+            let rules = fleet::variant_rules(&variant)?;
+            duel::host(&addr, rules, fleet)?;
+        }
+        Command::Connect { addr, fleet } => duel::connect(&addr, fleet)?,
+    }
+    Ok(())
+}
+
+fn game_view(backend: &impl Backend, name: &str) -> Result<GameView> {
+    backend
+        .game_state(name)?
+        .ok_or_else(|| anyhow!("no such game: {}", name))
+}
+
+fn setup(
+    store: &Store,
+    name: &str,
+    player: u32,
+    rules: GameRules,
+    fleet: Option<String>,
+) -> Result<SavedGame> {
+    if store.exists(name)? {
+        // Retry a setup the contract never saw with the fleet it was proved
+        // for, rather than risk replacing one it did accept.
+        let mut game = store.load(name)?;
+        if !matches!(game.pending, Some(Pending::Setup)) || game.player != player {
+            bail!("already playing {}", name);
+        }
+        game.shots.clear();
+        return Ok(game);
+    }
+    let ships = match fleet {
+        Some(fleet) => fleet::parse_fleet(&fleet)?,
+        None => fleet::random_fleet(&rules),
+    };
+    Ok(SavedGame {
+        name: name.to_string(),
+        player,
+        state: fleet::new_game_state(rules, ships)?,
+        shots: Vec::new(),
+        pending: Some(Pending::Setup),
+    })
+}
+
+// Resolve a move that was saved but may not have reached the contract: keep it
+// if the contract holds the board it commits us to, otherwise roll the turn
+// back for a retry. The board moves on even with the turn that sinks our
+// last ship, which records no shot.
+fn settle(store: &Store, player: &str, game: &mut SavedGame, view: &GameView) -> Result<()> {
+    let pending = match game.pending.take() {
+        Some(pending) => pending,
+        None => return Ok(()),
+    };
+    let ours = view.player(game.player);
+    match pending {
+        Pending::Setup if ours.id == player => {}
+        Pending::Setup => bail!(
+            "the contract never accepted our fleet for {}, run the setup again",
+            game.name
+        ),
+        Pending::Turn { board, .. } if ours.id == player && ours.board == board => {}
+        Pending::Turn { prior, .. } => {
+            game.state = prior;
+            game.shots.pop();
+        }
+    }
+    store.save(game)
+}
+
+// On our turn the contract holds the outcome of our previous shot.
+fn record_last_hit(game: &mut SavedGame, view: &GameView) {
+    if let Some((_, hit @ None)) = game.shots.last_mut() {
+        *hit = Some(view.last_hit());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use battleship_core::Position;
+
+    use super::*;
+    use backend::PlayerView;
+
+    fn player(id: &str, board: u32) -> PlayerView {
+        PlayerView {
+            id: id.to_string(),
+            board: [board; 8],
+            shot_x: 0,
+            shot_y: 0,
+        }
+    }
+
+    #[test]
+    fn pending() {
+        let dir = std::env::temp_dir().join(format!("battleship-{:016x}", rand::random::<u64>()));
+        let store = Store::new(&dir);
+        let rules = GameRules::default();
+        let prior = fleet::new_game_state(rules.clone(), fleet::random_fleet(&rules)).unwrap();
+        let mut after = prior.clone();
+        after.salt = [1; 8];
+        let mut game = SavedGame {
+            name: "duel".to_string(),
+            player: 2,
+            state: after.clone(),
+            shots: vec![(Position::new(1, 1), None)],
+            pending: None,
+        };
+
+        // Our last ship sank with this turn: the contract ended the game
+        // without recording our shot, but our board moved on
+        let mut view = GameView {
+            next_turn: 3,
+            rules,
+            p1: player("alice", 0),
+            p2: player("bob", 2),
+            last_hit: 2,
+            sunk_what: 0,
+            winner: Some("alice".to_string()),
+            opponent: None,
+        };
+        game.pending = Some(Pending::Turn {
+            prior: prior.clone(),
+            board: [2; 8],
+        });
+        settle(&store, "bob", &mut game, &view).unwrap();
+        assert!(game.pending.is_none());
+        assert_eq!(game.state, after);
+        assert_eq!(game.shots.len(), 1);
+
+        // A turn the contract never saw is rolled back
+        view.p2.board = [1; 8];
+        game.pending = Some(Pending::Turn {
+            prior: prior.clone(),
+            board: [2; 8],
+        });
+        settle(&store, "bob", &mut game, &view).unwrap();
+        assert!(game.pending.is_none());
+        assert_eq!(game.state, prior);
+        assert!(game.shots.is_empty());
+        assert_eq!(store.load("duel").unwrap().state, prior);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Context, Result};
+
+use battleship_core::RoundCommit;
+use battleship_methods::TURN_ID;
+use battleship_protocol::decode_receipt;
+use battleship_prover::LocalProver;
+
+// Proving runs in this process, so the fleet never leaves the machine
+pub fn load() -> Result<LocalProver> {
+    LocalProver::load().context("failed to load the guest methods")
+}
+
+// The board the contract stores for us once it accepts this turn receipt
+pub fn board_after(receipt: &str) -> Result<[u32; 8]> {
+    let commit: RoundCommit = decode_receipt(receipt, TURN_ID, false)?;
+    Ok(commit.new_state.as_slice().try_into()?)
+}
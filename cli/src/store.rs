@@ -0,0 +1,114 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use battleship_core::{GameState, HitType, Position};
+
+// Everything a player has to keep secret between moves. Losing it means
+// losing the game, since the salt cannot be recovered.
+#[derive(Deserialize, Serialize)]
+pub struct SavedGame {
+    pub name: String,
+    pub player: u32, // 1 created the game, 2 joined it
+    pub state: GameState,
+    pub shots: Vec<(Position, Option<HitType>)>, // Our shots, None until answered
+    #[serde(default)]
+    pub pending: Option<Pending>, // Our last move, until the contract has it
+}
+
+// A move saved before it is sent to the contract, so that a crash or a failed
+// call never leaves the contract holding a commitment we have no secret for.
+#[derive(Deserialize, Serialize)]
+pub enum Pending {
+    Setup,
+    Turn {
+        prior: GameState, // The state the turn was proved from
+        board: [u32; 8],  // Our board on the contract once it has the turn
+    },
+}
+
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub fn new(dir: &Path) -> Self {
+        Store {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    // Game names come from the command line and the contract, so keep them
+    // from naming a file outside the state directory.
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || name.contains(|c: char| matches!(c, '/' | '\\' | ':') || c.is_control())
+        {
+            bail!("{:?} cannot be used as a local game name", name);
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    pub fn load(&self, name: &str) -> Result<SavedGame> {
+        let path = self.path(name)?;
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("no local state for {} at {}", name, path.display()))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn save(&self, game: &SavedGame) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(&game.name)?, serde_json::to_string_pretty(game)?)?;
+        Ok(())
+    }
+
+    pub fn exists(&self, name: &str) -> Result<bool> {
+        Ok(self.path(name)?.exists())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let store = Store::new(Path::new("games"));
+        assert_eq!(
+            store.path("duel-1").unwrap(),
+            Path::new("games/duel-1.json")
+        );
+        for name in [
+            "",
+            ".",
+            "..",
+            "../duel",
+            "a/b",
+            "a\\b",
+            "/etc/passwd",
+            "c:duel",
+            "a\nb",
+        ] {
+            assert!(store.path(name).is_err(), "{:?}", name);
+        }
+    }
+}
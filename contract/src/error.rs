@@ -0,0 +1,44 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use near_sdk::env;
+
+pub use battleship_protocol::BattleshipError;
+
+// Contract calls report errors by panicking with "<code>: <message>"
+pub trait Panic {
+    fn panic(self) -> !;
+}
+
+impl Panic for BattleshipError {
+    fn panic(self) -> ! {
+        env::panic(format!("{}: {}", self.code(), self).as_bytes())
+    }
+}
+
+pub fn require(cond: bool, err: BattleshipError) {
+    if !cond {
+        err.panic();
+    }
+}
+
+pub trait OrPanic<T> {
+    fn or_panic(self) -> T;
+}
+
+impl<T> OrPanic<T> for Result<T, BattleshipError> {
+    fn or_panic(self) -> T {
+        self.unwrap_or_else(|err| err.panic())
+    }
+}
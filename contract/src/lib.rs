@@ -12,63 +12,67 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use arrayref::array_ref;
+mod error;
+
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     collections::UnorderedMap,
-    env, near_bindgen, AccountId,
+    env, near_bindgen, AccountId, PanicOnDefault,
 };
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 
-use risc0_zkvm_core::Digest;
-use risc0_zkvm_verify::zkvm::{MethodID, Receipt};
+use battleship_core::{InitCommit, Position, RevealCommit, RoundCommit};
+use battleship_methods::{INIT_ID, REVEAL_ID, TURN_ID};
+use battleship_protocol::{verify_receipt, Referee};
+pub use battleship_protocol::{GameState, PlayerState, RevealedShip, Rules};
 
-use battleship_core::{HitType, RoundCommit};
-use battleship_methods::{INIT_ID, TURN_ID};
+pub use error::BattleshipError;
+use error::{require, OrPanic, Panic};
 
-#[derive(Default, Deserialize, Serialize, BorshDeserialize, BorshSerialize)]
-pub struct PlayerState {
-    id: AccountId,
-    board: [u32; 8],
-    shot_x: u32,
-    shot_y: u32,
+fn now_ms() -> u64 {
+    env::block_timestamp() / 1_000_000
 }
 
-#[derive(Default, Deserialize, Serialize, BorshDeserialize, BorshSerialize)]
-pub struct GameState {
-    // 0 means p1 has setup game, and p2 needs to do setup
-    // 1 means p1 needs to process p2's shot and make it's own
-    // 2 means p2 needs to process p1's shot and make it's own
-    next_turn: u32,
-    p1: PlayerState,
-    p2: PlayerState,
-    last_hit: u8,  // 0 = miss, 1 = hit, 2 = sunk
-    sunk_what: u8, // Which ship was sunk
+fn referee() -> Referee {
+    Referee::new(now_ms())
 }
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct BattleshipContract {
+    owner: AccountId,
     games: UnorderedMap<String, GameState>,
 }
 
-impl Default for BattleshipContract {
-    fn default() -> Self {
-        BattleshipContract {
-            games: UnorderedMap::<String, GameState>::new(0 as u8),
-        }
+impl BattleshipContract {
+    fn get_game(&self, name: &String) -> GameState {
+        self.games
+            .get(name)
+            .unwrap_or_else(|| BattleshipError::GameNotFound.panic())
     }
-}
 
-pub fn verify_receipt(str: &String, method_id: &MethodID) -> Vec<u32> {
-    let as_bytes = base64::decode(str).unwrap();
-    let receipt = bincode::deserialize::<Receipt>(&as_bytes).unwrap();
-    receipt.verify(&method_id).unwrap();
-    receipt.get_journal_u32()
+    fn verify<T: DeserializeOwned>(receipt_str: &String, method_id: &[u8]) -> T {
+        verify_receipt(receipt_str, method_id)
+            .map_err(BattleshipError::from)
+            .or_panic()
+    }
 }
 
 #[near_bindgen]
 impl BattleshipContract {
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        BattleshipContract {
+            owner,
+            games: UnorderedMap::<String, GameState>::new(0 as u8),
+        }
+    }
+
+    pub fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+
     // View state of a game
     pub fn game_state(&self, name: String) -> Option<GameState> {
         self.games.get(&name)
@@ -78,38 +82,51 @@ impl BattleshipContract {
         self.games.keys().collect()
     }
 
+    // The owner may delete any game, see `Referee::may_delete` for everyone else
     pub fn delete_game(&mut self, name: String) {
+        // Get game record (panic if not there)
+        let state = self.get_game(&name);
+        let signer = env::signer_account_id();
+        require(
+            signer == self.owner || referee().may_delete(&state, &signer),
+            BattleshipError::NotAllowed,
+        );
         self.games.remove(&name);
     }
 
+    // Only the contract owner may wipe every game
     pub fn clear_games(&mut self) {
+        require(
+            env::signer_account_id() == self.owner,
+            BattleshipError::NotAllowed,
+        );
         self.games.clear();
     }
 
-    // Set's p1's initial state
-    pub fn new_game(&mut self, name: String, receipt_str: String) -> GameState {
+    // Only `opponent` may join the game, if given
+    pub fn new_game(
+        &mut self,
+        name: String,
+        receipt_str: String,
+        opponent: Option<AccountId>,
+        turn_timeout_secs: Option<u64>,
+    ) -> GameState {
         // Game must not exist
-        assert!(self.games.get(&name).is_none());
-        let method_id = MethodID::try_from(INIT_ID).unwrap();
-        let journal = verify_receipt(&receipt_str, &method_id);
-        let digest = risc0_zkvm_serde::from_slice::<Digest>(&journal).unwrap();
-        let state = GameState {
-            next_turn: 0,
-            p1: PlayerState {
-                id: env::signer_account_id(),
-                board: *array_ref![digest.as_slice(), 0, 8],
-                shot_x: 0,
-                shot_y: 0,
-            },
-            p2: PlayerState::default(),
-            last_hit: 0,
-            sunk_what: 0,
-        };
+        require(self.games.get(&name).is_none(), BattleshipError::GameExists);
+        let commit: InitCommit = Self::verify(&receipt_str, INIT_ID);
+        let state = referee()
+            .new_game(
+                &name,
+                &env::signer_account_id(),
+                commit,
+                opponent,
+                turn_timeout_secs,
+            )
+            .or_panic();
         self.games.insert(&name, &state);
         state
     }
 
-    // Set's p2's state, and makes the first shot at p1
     pub fn join_game(
         &mut self,
         name: String,
@@ -118,28 +135,18 @@ impl BattleshipContract {
         shot_y: u32,
     ) -> GameState {
         // Get game record (panic if not there)
-        let mut state = self.games.get(&name).unwrap();
-        // Verify we are are on turn 0
-        assert!(state.next_turn == 0);
-        // Set turn to 1
-        state.next_turn = 1;
+        let mut state = self.get_game(&name);
         // Verify the player has a valid initial state
-        let method_id = MethodID::try_from(INIT_ID).unwrap();
-        let journal = verify_receipt(&receipt_str, &method_id);
-        let digest = risc0_zkvm_serde::from_slice::<Digest>(&journal).unwrap();
-        // Update player 2 starting state + set shot
-        state.p2 = PlayerState {
-            id: env::signer_account_id(),
-            board: *array_ref![digest.as_slice(), 0, 8],
-            shot_x,
-            shot_y,
-        };
+        let commit: InitCommit = Self::verify(&receipt_str, INIT_ID);
+        let shot = Position::new(shot_x, shot_y);
+        referee()
+            .join_game(&mut state, &name, &env::signer_account_id(), commit, shot)
+            .or_panic();
         // Write back to contract
         self.games.insert(&name, &state);
         state
     }
 
-    // Do a normal turn
     pub fn turn(
         &mut self,
         name: String,
@@ -148,46 +155,35 @@ impl BattleshipContract {
         shot_y: u32,
     ) -> GameState {
         // Get game record (panic if not there)
-        let mut state = self.games.get(&name).unwrap();
-        // Verify we are are on turn 1 or 2
-        assert!(state.next_turn >= 1);
-        // Get ref to player current player (responding prior shot, making new one)
-        let (cur_player, prev_player) = if state.next_turn == 1 {
-            (&mut state.p1, &mut state.p2)
-        } else {
-            (&mut state.p2, &mut state.p1)
-        };
-        // Verify the right user is playing
-        assert!(cur_player.id == env::signer_account_id());
+        let mut state = self.get_game(&name);
         // Verify the proof and extract as a RoundCommit
-        let method_id = MethodID::try_from(TURN_ID).unwrap();
-        let journal = verify_receipt(&receipt_str, &method_id);
-        let commit = risc0_zkvm_serde::from_slice::<RoundCommit>(&journal).unwrap();
-        // Make sure the prior state matches the current state
-        assert!(cur_player.board.as_slice() == commit.old_state.as_slice());
-        // Make sure the response matches the prior shot
-        assert!(commit.shot.x == prev_player.shot_x);
-        assert!(commit.shot.y == prev_player.shot_y);
-        // Set the hit status
-        match commit.hit {
-            HitType::Miss => {
-                state.last_hit = 0;
-            }
-            HitType::Hit => {
-                state.last_hit = 1;
-            }
-            HitType::Sunk(ship) => {
-                state.last_hit = 2;
-                state.sunk_what = ship;
-            }
-        };
-        // Update the current players state
-        cur_player.board = *array_ref![commit.new_state.as_slice(), 0, 8];
-        // Set the new shot
-        cur_player.shot_x = shot_x;
-        cur_player.shot_y = shot_y;
-        // Update which player's turn it is
-        state.next_turn = 3 - state.next_turn;
+        let commit: RoundCommit = Self::verify(&receipt_str, TURN_ID);
+        let shot = Position::new(shot_x, shot_y);
+        referee()
+            .turn(&mut state, &name, &env::signer_account_id(), commit, shot)
+            .or_panic();
+        // Write back to contract
+        self.games.insert(&name, &state);
+        state
+    }
+
+    pub fn claim_forfeit(&mut self, name: String) -> GameState {
+        // Get game record (panic if not there)
+        let mut state = self.get_game(&name);
+        referee()
+            .claim_forfeit(&mut state, &env::signer_account_id())
+            .or_panic();
+        // Write back to contract
+        self.games.insert(&name, &state);
+        state
+    }
+
+    pub fn reveal(&mut self, name: String, receipt_str: String) -> GameState {
+        // Get game record (panic if not there)
+        let mut state = self.get_game(&name);
+        // Verify the proof and extract as a RevealCommit
+        let commit: RevealCommit = Self::verify(&receipt_str, REVEAL_ID);
+        referee().reveal(&mut state, commit).or_panic();
         // Write back to contract
         self.games.insert(&name, &state);
         state
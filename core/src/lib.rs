@@ -14,14 +14,23 @@
 
 #![cfg_attr(not(test), no_std)]
 
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
 use core::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
 use risc0_zkvm_core::Digest;
 
+// Limits shared by every set of rules: the shot map holds MAX_BOARD_SIZE^2
+// cells and a ship's hit_mask holds MAX_SHIP_SPAN bits.
+pub const MAX_BOARD_SIZE: usize = 10;
+pub const MAX_SHIP_SPAN: usize = 8;
+pub const MAX_SHIPS: usize = 10;
+
+// The standard fleet on a MAX_BOARD_SIZE board.
 pub const NUM_SHIPS: usize = 5;
-pub const BOARD_SIZE: usize = 10;
 
 pub const SHIP_SPANS: [usize; NUM_SHIPS] = [
     5, // carrier
@@ -31,7 +40,17 @@ pub const SHIP_SPANS: [usize; NUM_SHIPS] = [
     2, // destroyer
 ];
 
-pub const SUNK_MASKS: [u8; NUM_SHIPS] = [0x1f, 0x0f, 0x07, 0x07, 0x03];
+// 256 bits of randomness hiding the board layout behind the committed digest.
+pub type Salt = [u32; 8];
+
+const SHOT_MAP_WORDS: usize = (MAX_BOARD_SIZE * MAX_BOARD_SIZE + 31) / 32;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GameRules {
+    pub width: u32,
+    pub height: u32,
+    pub spans: Vec<u32>,
+}
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Hash)]
 pub struct Position {
@@ -52,16 +71,40 @@ pub struct Ship {
     pub hit_mask: u8,
 }
 
+// One bit per board cell, set once the cell has been fired upon.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShotMap {
+    bits: [u32; SHOT_MAP_WORDS],
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GameState {
-    pub ships: [Ship; NUM_SHIPS],
-    pub salt: u32,
+    pub rules: GameRules,
+    pub ships: Vec<Ship>,
+    pub salt: Salt,
+    pub shots: ShotMap,
+}
+
+// The one seat a receipt is good for: a player in a named game. The init and
+// turn guests commit to it, so a receipt cannot be replayed into another game
+// or submitted by another player.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Seat {
+    pub game: String,
+    pub player: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct InitParams {
+    pub state: GameState,
+    pub seat: Seat,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RoundParams {
     pub state: GameState,
     pub shot: Position,
+    pub seat: Seat,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Hash)]
@@ -69,29 +112,61 @@ pub enum HitType {
     Miss,
     Hit,
     Sunk(u8),
+    // The shot is off the board or at a cell already fired at; the board is
+    // left unchanged and the shooter loses the shot.
+    InvalidShot,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RoundResult {
     pub state: GameState,
     pub hit: HitType,
+    pub fleet_destroyed: bool,
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct InitCommit {
+    pub state: Digest,
+    pub rules: GameRules,
+    pub seat: Seat,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RoundCommit {
     pub old_state: Digest,
     pub new_state: Digest,
     pub shot: Position,
     pub hit: HitType,
+    pub fleet_destroyed: bool,
+    pub seat: Seat,
+    pub shots_taken: u32, // Cells fired at on the old board, ordering a player's rounds
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RevealCommit {
+    pub state: Digest,
+    pub ships: Vec<Ship>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GameError {
+    InvalidRules,
+    ShipCount { expected: u32, found: u32 },
+    ShipDamaged(u32),
+    ShipOutOfBounds(u32),
+    ShipOverlap(u32),
+    ShotsTaken,
+    ShotOutOfBounds(Position),
+    DuplicateShot(Position),
 }
 
 pub struct GameCheck {
-    board: [[bool; BOARD_SIZE]; BOARD_SIZE],
+    board: [[bool; MAX_BOARD_SIZE]; MAX_BOARD_SIZE],
 }
 
 impl GameCheck {
     pub fn new() -> Self {
-        let board: [[bool; BOARD_SIZE]; BOARD_SIZE] = [[false; BOARD_SIZE]; BOARD_SIZE];
+        let board = [[false; MAX_BOARD_SIZE]; MAX_BOARD_SIZE];
         GameCheck { board }
     }
 
@@ -137,42 +212,164 @@ impl GameCheck {
     }
 }
 
+impl GameRules {
+    pub fn new(width: u32, height: u32, spans: Vec<u32>) -> Self {
+        GameRules {
+            width,
+            height,
+            spans,
+        }
+    }
+
+    pub fn check(&self) -> bool {
+        let size = 1..=MAX_BOARD_SIZE as u32;
+        size.contains(&self.width)
+            && size.contains(&self.height)
+            && (1..=MAX_SHIPS).contains(&self.spans.len())
+            && self.spans.iter().all(|span| {
+                *span >= 1
+                    && *span as usize <= MAX_SHIP_SPAN
+                    && (*span <= self.width || *span <= self.height)
+            })
+    }
+
+    pub fn num_ships(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn span(&self, ship: usize) -> usize {
+        self.spans[ship] as usize
+    }
+
+    pub fn sunk_mask(&self, ship: usize) -> u8 {
+        ((1u16 << self.span(ship)) - 1) as u8
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::new(
+            MAX_BOARD_SIZE as u32,
+            MAX_BOARD_SIZE as u32,
+            SHIP_SPANS.iter().map(|span| *span as u32).collect(),
+        )
+    }
+}
+
 impl GameState {
+    pub fn new(rules: GameRules, ships: Vec<Ship>, salt: Salt) -> Self {
+        GameState {
+            rules,
+            ships,
+            salt,
+            shots: ShotMap::default(),
+        }
+    }
+
     pub fn check(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    // Checks that this is a fresh board: valid rules, one undamaged ship per
+    // span, every ship on the board, no overlaps and no shots taken yet.
+    pub fn validate(&self) -> Result<(), GameError> {
+        self.validate_fleet()?;
+        if self.shots != ShotMap::default() {
+            return Err(GameError::ShotsTaken);
+        }
         let mut game_check = GameCheck::new();
-        for i in 0..NUM_SHIPS {
-            let ship = &self.ships[i];
-            let span = SHIP_SPANS[i];
-            if !ship.check(span) {
-                return false;
+        for (i, ship) in self.ships.iter().enumerate() {
+            let span = self.rules.span(i);
+            if ship.hit_mask != 0 {
+                return Err(GameError::ShipDamaged(i as u32));
+            }
+            if !ship.check(span, &self.rules) {
+                return Err(GameError::ShipOutOfBounds(i as u32));
             }
-            if !game_check.check(&ship, span, true) {
-                return false;
+            if !game_check.check(ship, span, true) {
+                return Err(GameError::ShipOverlap(i as u32));
             }
         }
-        true
+        Ok(())
+    }
+
+    fn validate_fleet(&self) -> Result<(), GameError> {
+        if !self.rules.check() {
+            return Err(GameError::InvalidRules);
+        }
+        if self.ships.len() != self.rules.num_ships() {
+            return Err(GameError::ShipCount {
+                expected: self.rules.num_ships() as u32,
+                found: self.ships.len() as u32,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn fleet_destroyed(&self) -> bool {
+        self.ships
+            .iter()
+            .enumerate()
+            .all(|(i, ship)| ship.hit_mask == self.rules.sunk_mask(i))
+    }
+}
+
+impl Seat {
+    pub fn new(game: &str, player: &str) -> Self {
+        Seat {
+            game: game.into(),
+            player: player.into(),
+        }
+    }
+}
+
+impl InitParams {
+    pub fn new(state: GameState, seat: Seat) -> Self {
+        InitParams { state, seat }
     }
 }
 
 impl RoundParams {
-    pub fn new(state: GameState, x: u32, y: u32) -> Self {
+    pub fn new(state: GameState, x: u32, y: u32, seat: Seat) -> Self {
         RoundParams {
             state,
             shot: Position::new(x, y),
+            seat,
+        }
+    }
+
+    // Checks that this state can answer a shot. Shots failing `check_shot`
+    // are answered with HitType::InvalidShot rather than rejected.
+    pub fn validate(&self) -> Result<(), GameError> {
+        self.state.validate_fleet()
+    }
+
+    // Checks that the shot is on the board and at a fresh cell.
+    pub fn check_shot(&self) -> Result<(), GameError> {
+        if !self.shot.check(&self.state.rules) {
+            return Err(GameError::ShotOutOfBounds(self.shot.clone()));
+        }
+        if self.state.shots.contains(&self.shot) {
+            return Err(GameError::DuplicateShot(self.shot.clone()));
         }
+        Ok(())
     }
 
     pub fn process(&self) -> RoundResult {
         let mut state = self.state.clone();
         let shot = &self.shot;
-        assert!(shot.check());
+        if self.check_shot().is_err() {
+            return RoundResult::new(state, HitType::InvalidShot);
+        }
+        state.shots.insert(shot);
         enum HitShift {
             Hit(u32),
             Miss,
         }
-        for i in 0..NUM_SHIPS {
+        for i in 0..state.ships.len() {
+            let span = state.rules.span(i) as u32;
+            let sunk_mask = state.rules.sunk_mask(i);
             let ship = &mut state.ships[i];
-            let span = SHIP_SPANS[i] as u32;
             let x = ship.pos.x;
             let y = ship.pos.y;
             let hit_shift = match ship.dir {
@@ -193,7 +390,7 @@ impl RoundParams {
             };
             if let HitShift::Hit(shift) = hit_shift {
                 ship.hit_mask |= 1 << shift;
-                let hit = if ship.hit_mask == SUNK_MASKS[i] {
+                let hit = if ship.hit_mask == sunk_mask {
                     HitType::Sunk(i as u8)
                 } else {
                     HitType::Hit
@@ -201,16 +398,64 @@ impl RoundParams {
                 return RoundResult::new(state, hit);
             }
         }
+        RoundResult::new(state, HitType::Miss)
+    }
+}
+
+impl RoundResult {
+    pub fn new(state: GameState, hit: HitType) -> Self {
+        let fleet_destroyed = state.fleet_destroyed();
         RoundResult {
             state,
-            hit: HitType::Miss,
+            hit,
+            fleet_destroyed,
         }
     }
 }
 
-impl RoundResult {
-    pub fn new(state: GameState, hit: HitType) -> Self {
-        RoundResult { state, hit }
+impl RoundCommit {
+    // Cells fired at on the new board: an invalid shot leaves it unchanged.
+    pub fn shots_after(&self) -> u32 {
+        self.shots_taken + (self.hit != HitType::InvalidShot) as u32
+    }
+}
+
+impl ShotMap {
+    // None for cells the map has no room for, which no board can have.
+    fn index(pos: &Position) -> Option<(usize, u32)> {
+        let size = MAX_BOARD_SIZE as u32;
+        if pos.x >= size || pos.y >= size {
+            return None;
+        }
+        let cell = (pos.y * size + pos.x) as usize;
+        Some((cell / 32, 1 << (cell % 32)))
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        match ShotMap::index(pos) {
+            Some((word, bit)) => self.bits[word] & bit != 0,
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.bits.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Marks the cell as fired upon, returning false if it already was or
+    // lies outside every board.
+    pub fn insert(&mut self, pos: &Position) -> bool {
+        let (word, bit) = match ShotMap::index(pos) {
+            Some(index) => index,
+            None => return false,
+        };
+        let fresh = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        fresh
     }
 }
 
@@ -219,8 +464,8 @@ impl Position {
         Position { x, y }
     }
 
-    pub fn check(&self) -> bool {
-        self.x < BOARD_SIZE as u32 && self.y < BOARD_SIZE as u32
+    pub fn check(&self, rules: &GameRules) -> bool {
+        self.x < rules.width && self.y < rules.height
     }
 }
 
@@ -230,6 +475,23 @@ impl Display for Position {
     }
 }
 
+impl Display for GameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GameError::InvalidRules => write!(f, "invalid game rules"),
+            GameError::ShipCount { expected, found } => {
+                write!(f, "expected {} ships, found {}", expected, found)
+            }
+            GameError::ShipDamaged(ship) => write!(f, "ship {} is already damaged", ship),
+            GameError::ShipOutOfBounds(ship) => write!(f, "ship {} is out of bounds", ship),
+            GameError::ShipOverlap(ship) => write!(f, "ship {} overlaps another ship", ship),
+            GameError::ShotsTaken => write!(f, "shots already taken on a new board"),
+            GameError::ShotOutOfBounds(pos) => write!(f, "shot {} is out of bounds", pos),
+            GameError::DuplicateShot(pos) => write!(f, "shot {} was already taken", pos),
+        }
+    }
+}
+
 impl Ship {
     pub fn new(x: u32, y: u32, dir: ShipDirection) -> Self {
         Ship {
@@ -247,13 +509,17 @@ impl Ship {
         }
     }
 
-    pub fn check(&self, span: usize) -> bool {
-        let limit = BOARD_SIZE - span;
+    pub fn check(&self, span: usize, rules: &GameRules) -> bool {
+        let span = span as u32;
         self.hit_mask == 0
-            && self.pos.check()
+            && self.pos.check(rules)
             && match self.dir {
-                ShipDirection::Horizontal => self.pos.x as usize <= limit,
-                ShipDirection::Vertical => self.pos.y as usize <= limit,
+                ShipDirection::Horizontal => {
+                    span <= rules.width && self.pos.x <= rules.width - span
+                }
+                ShipDirection::Vertical => {
+                    span <= rules.height && self.pos.y <= rules.height - span
+                }
             }
     }
 }
@@ -262,17 +528,27 @@ impl Ship {
 mod tests {
     use super::*;
 
+    fn shots(cells: &[(u32, u32)]) -> ShotMap {
+        let mut shots = ShotMap::default();
+        for (x, y) in cells {
+            shots.insert(&Position::new(*x, *y));
+        }
+        shots
+    }
+
     #[test]
     fn basic() {
         let state = GameState {
-            ships: [
+            rules: GameRules::default(),
+            ships: vec![
                 Ship::new(2, 3, ShipDirection::Vertical),
                 Ship::new(3, 1, ShipDirection::Horizontal),
                 Ship::new(4, 7, ShipDirection::Vertical),
                 Ship::new(7, 5, ShipDirection::Horizontal),
                 Ship::new(7, 7, ShipDirection::Horizontal),
             ],
-            salt: 0xDEADBEEF,
+            salt: [0xDEADBEEF; 8],
+            shots: ShotMap::default(),
         };
 
         assert!(state.check());
@@ -281,17 +557,90 @@ mod tests {
     #[test]
     fn overlap() {
         let state = GameState {
-            ships: [
+            rules: GameRules::default(),
+            ships: vec![
                 Ship::new(2, 3, ShipDirection::Vertical),
                 Ship::new(3, 1, ShipDirection::Horizontal),
                 Ship::new(2, 3, ShipDirection::Vertical),
                 Ship::new(7, 5, ShipDirection::Horizontal),
                 Ship::new(7, 7, ShipDirection::Horizontal),
             ],
-            salt: 0xDEADBEEF,
+            salt: [0xDEADBEEF; 8],
+            shots: ShotMap::default(),
         };
 
         assert!(!state.check());
+        assert_eq!(state.validate(), Err(GameError::ShipOverlap(2)));
+    }
+
+    #[test]
+    fn validate() {
+        let rules = GameRules::new(8, 8, vec![4, 3, 3, 2]);
+        let state = GameState::new(
+            rules,
+            vec![
+                Ship::new(0, 0, ShipDirection::Horizontal),
+                Ship::new(7, 1, ShipDirection::Vertical),
+                Ship::new(2, 5, ShipDirection::Horizontal),
+                Ship::new(5, 7, ShipDirection::Horizontal),
+            ],
+            [0xDEADBEEF; 8],
+        );
+        assert_eq!(state.validate(), Ok(()));
+
+        let mut bad = state.clone();
+        bad.ships[1] = Ship::new(7, 6, ShipDirection::Vertical);
+        assert_eq!(bad.validate(), Err(GameError::ShipOutOfBounds(1)));
+        let mut bad = state.clone();
+        bad.ships.push(Ship::new(0, 2, ShipDirection::Vertical));
+        assert_eq!(
+            bad.validate(),
+            Err(GameError::ShipCount {
+                expected: 4,
+                found: 5
+            })
+        );
+        let mut bad = state.clone();
+        bad.ships[0].hit_mask = 0x01;
+        assert_eq!(bad.validate(), Err(GameError::ShipDamaged(0)));
+        let mut bad = state.clone();
+        bad.shots = shots(&[(1, 1)]);
+        assert_eq!(bad.validate(), Err(GameError::ShotsTaken));
+
+        let params = RoundParams::new(state.clone(), 8, 0, Seat::default());
+        assert_eq!(params.validate(), Ok(()));
+        assert_eq!(
+            params.check_shot(),
+            Err(GameError::ShotOutOfBounds(Position::new(8, 0)))
+        );
+        let result = RoundParams::new(state, 1, 1, Seat::default()).process();
+        let params = RoundParams::new(result.state, 1, 1, Seat::default());
+        assert_eq!(
+            params.check_shot(),
+            Err(GameError::DuplicateShot(Position::new(1, 1)))
+        );
+        assert_eq!(params.state.shots.len(), 1);
+    }
+
+    #[test]
+    fn shot_map() {
+        let mut shots = ShotMap::default();
+        let edge = MAX_BOARD_SIZE as u32 - 1;
+        for pos in [Position::new(0, 0), Position::new(edge, edge)] {
+            assert!(!shots.contains(&pos));
+            assert!(shots.insert(&pos));
+            assert!(shots.contains(&pos));
+            assert!(!shots.insert(&pos));
+        }
+        for pos in [
+            Position::new(edge + 1, 0),
+            Position::new(0, edge + 1),
+            Position::new(u32::MAX, u32::MAX),
+        ] {
+            assert!(!shots.insert(&pos));
+            assert!(!shots.contains(&pos));
+        }
+        assert_eq!(shots.len(), 2);
     }
 
     #[test]
@@ -309,9 +658,10 @@ mod tests {
         // 8|         C           |
         // 9|         C           |
 
-        let salt = 0xDEADBEEF;
+        let salt = [0xDEADBEEF; 8];
         let state1 = GameState {
-            ships: [
+            rules: GameRules::default(),
+            ships: vec![
                 Ship::new(2, 3, ShipDirection::Vertical),
                 Ship::new(3, 1, ShipDirection::Horizontal),
                 Ship::new(4, 7, ShipDirection::Vertical),
@@ -319,16 +669,24 @@ mod tests {
                 Ship::new(7, 7, ShipDirection::Horizontal),
             ],
             salt,
+            shots: ShotMap::default(),
         };
 
-        let params1 = RoundParams::new(state1.clone(), 1, 1);
-        let result1 = RoundResult::new(state1.clone(), HitType::Miss);
+        let params1 = RoundParams::new(state1.clone(), 1, 1, Seat::default());
+        let result1 = RoundResult::new(
+            GameState {
+                shots: shots(&[(1, 1)]),
+                ..state1.clone()
+            },
+            HitType::Miss,
+        );
         assert_eq!(params1.process(), result1);
 
-        let params2 = RoundParams::new(state1.clone(), 4, 1);
+        let params2 = RoundParams::new(state1.clone(), 4, 1, Seat::default());
         let result2 = RoundResult::new(
             GameState {
-                ships: [
+                rules: GameRules::default(),
+                ships: vec![
                     Ship::new(2, 3, ShipDirection::Vertical),
                     Ship::with_hit_mask(3, 1, ShipDirection::Horizontal, 0x02),
                     Ship::new(4, 7, ShipDirection::Vertical),
@@ -336,20 +694,17 @@ mod tests {
                     Ship::new(7, 7, ShipDirection::Horizontal),
                 ],
                 salt,
+                shots: shots(&[(4, 1)]),
             },
             HitType::Hit,
         );
         assert_eq!(params2.process(), result2);
 
-        // Duplicate hit results in no state change
-        let params3 = RoundParams::new(state1, 4, 1);
-        let result3 = result2.clone();
-        assert_eq!(params3.process(), result3);
-
-        let params4 = RoundParams::new(result3.state, 3, 1);
+        let params4 = RoundParams::new(result2.state, 3, 1, Seat::default());
         let result4 = RoundResult::new(
             GameState {
-                ships: [
+                rules: GameRules::default(),
+                ships: vec![
                     Ship::new(2, 3, ShipDirection::Vertical),
                     Ship::with_hit_mask(3, 1, ShipDirection::Horizontal, 0x03),
                     Ship::new(4, 7, ShipDirection::Vertical),
@@ -357,15 +712,17 @@ mod tests {
                     Ship::new(7, 7, ShipDirection::Horizontal),
                 ],
                 salt,
+                shots: shots(&[(4, 1), (3, 1)]),
             },
             HitType::Hit,
         );
         assert_eq!(params4.process(), result4);
 
-        let params5 = RoundParams::new(result4.state, 6, 1);
+        let params5 = RoundParams::new(result4.state, 6, 1, Seat::default());
         let result5 = RoundResult::new(
             GameState {
-                ships: [
+                rules: GameRules::default(),
+                ships: vec![
                     Ship::new(2, 3, ShipDirection::Vertical),
                     Ship::with_hit_mask(3, 1, ShipDirection::Horizontal, 0x0b),
                     Ship::new(4, 7, ShipDirection::Vertical),
@@ -373,15 +730,17 @@ mod tests {
                     Ship::new(7, 7, ShipDirection::Horizontal),
                 ],
                 salt,
+                shots: shots(&[(4, 1), (3, 1), (6, 1)]),
             },
             HitType::Hit,
         );
         assert_eq!(params5.process(), result5);
 
-        let params6 = RoundParams::new(result5.state, 5, 1);
+        let params6 = RoundParams::new(result5.state, 5, 1, Seat::default());
         let result6 = RoundResult::new(
             GameState {
-                ships: [
+                rules: GameRules::default(),
+                ships: vec![
                     Ship::new(2, 3, ShipDirection::Vertical),
                     Ship::with_hit_mask(3, 1, ShipDirection::Horizontal, 0x0f),
                     Ship::new(4, 7, ShipDirection::Vertical),
@@ -389,9 +748,112 @@ mod tests {
                     Ship::new(7, 7, ShipDirection::Horizontal),
                 ],
                 salt,
+                shots: shots(&[(4, 1), (3, 1), (6, 1), (5, 1)]),
             },
             HitType::Sunk(1),
         );
         assert_eq!(params6.process(), result6);
     }
+
+    #[test]
+    fn invalid_shot() {
+        let state = GameState::new(
+            GameRules::default(),
+            vec![
+                Ship::new(2, 3, ShipDirection::Vertical),
+                Ship::new(3, 1, ShipDirection::Horizontal),
+                Ship::new(4, 7, ShipDirection::Vertical),
+                Ship::new(7, 5, ShipDirection::Horizontal),
+                Ship::new(7, 7, ShipDirection::Horizontal),
+            ],
+            [0xDEADBEEF; 8],
+        );
+        assert!(state.check());
+
+        let result = RoundParams::new(state, 4, 1, Seat::default()).process();
+        assert!(!result.state.check());
+        let again = RoundParams::new(result.state.clone(), 4, 1, Seat::default()).process();
+        assert_eq!(again.hit, HitType::InvalidShot);
+        assert_eq!(again.state, result.state);
+        let off_board = RoundParams::new(result.state.clone(), 4, 10, Seat::default()).process();
+        assert_eq!(off_board.hit, HitType::InvalidShot);
+        assert_eq!(off_board.state, result.state);
+    }
+
+    #[test]
+    fn fleet_destroyed() {
+        let salt = [0xDEADBEEF; 8];
+        let state = GameState {
+            rules: GameRules::default(),
+            ships: vec![
+                Ship::with_hit_mask(2, 3, ShipDirection::Vertical, 0x1f),
+                Ship::with_hit_mask(3, 1, ShipDirection::Horizontal, 0x0f),
+                Ship::with_hit_mask(4, 7, ShipDirection::Vertical, 0x07),
+                Ship::with_hit_mask(7, 5, ShipDirection::Horizontal, 0x07),
+                Ship::with_hit_mask(7, 7, ShipDirection::Horizontal, 0x01),
+            ],
+            salt,
+            shots: ShotMap::default(),
+        };
+        assert!(!state.fleet_destroyed());
+
+        let result = RoundParams::new(state.clone(), 1, 1, Seat::default()).process();
+        assert_eq!(result.hit, HitType::Miss);
+        assert!(!result.fleet_destroyed);
+
+        let result = RoundParams::new(state, 8, 7, Seat::default()).process();
+        assert_eq!(result.hit, HitType::Sunk(4));
+        assert!(result.fleet_destroyed);
+    }
+
+    #[test]
+    fn rules() {
+        assert!(GameRules::default().check());
+        assert!(!GameRules::new(0, 10, vec![2]).check());
+        assert!(!GameRules::new(11, 10, vec![2]).check());
+        assert!(!GameRules::new(8, 8, vec![]).check());
+        assert!(!GameRules::new(8, 8, vec![9]).check());
+        assert_eq!(GameRules::default().sunk_mask(0), 0x1f);
+        assert_eq!(GameRules::default().sunk_mask(4), 0x03);
+
+        // Quick game
+        //  | 0 1 2 3 4 5 6 7 |
+        // 0| A A A A         |
+        // 1|               B |
+        // 2|               B |
+        // 3|               B |
+        // 4|                 |
+        // 5|     C C C       |
+        // 6|                 |
+        // 7|           D D   |
+        let rules = GameRules::new(8, 8, vec![4, 3, 3, 2]);
+        let state = GameState::new(
+            rules.clone(),
+            vec![
+                Ship::new(0, 0, ShipDirection::Horizontal),
+                Ship::new(7, 1, ShipDirection::Vertical),
+                Ship::new(2, 5, ShipDirection::Horizontal),
+                Ship::new(5, 7, ShipDirection::Horizontal),
+            ],
+            [0xDEADBEEF; 8],
+        );
+        assert!(state.check());
+
+        // Ships must fit the board and match the fleet
+        let mut bad = state.clone();
+        bad.ships[3] = Ship::new(7, 7, ShipDirection::Horizontal);
+        assert!(!bad.check());
+        let mut bad = state.clone();
+        bad.ships.pop();
+        assert!(!bad.check());
+
+        assert!(Position::new(7, 7).check(&rules));
+        assert!(!Position::new(8, 0).check(&rules));
+
+        let result = RoundParams::new(state, 6, 7, Seat::default()).process();
+        assert_eq!(result.hit, HitType::Hit);
+        let result = RoundParams::new(result.state, 5, 7, Seat::default()).process();
+        assert_eq!(result.hit, HitType::Sunk(3));
+        assert!(!result.fleet_destroyed);
+    }
 }
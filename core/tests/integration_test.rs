@@ -13,135 +13,15 @@
 // limitations under the License.
 
 use battleship_core::{
-    GameState, HitType, Position, RoundCommit, RoundParams, RoundResult, Ship, ShipDirection,
+    GameRules, GameState, HitType, Position, RevealCommit, RoundCommit, RoundParams, Seat, Ship,
+    ShipDirection, ShotMap,
 };
-use battleship_methods::{INIT_ID, INIT_PATH, TURN_ID, TURN_PATH};
+use battleship_methods::{REVEAL_ID, REVEAL_PATH};
 use log::LevelFilter;
 use risc0_zkvm_core::Digest;
-use risc0_zkvm_host::{Exception, Prover, Receipt, Result};
+use risc0_zkvm_host::Prover;
 use risc0_zkvm_serde::{from_slice, to_slice, to_vec};
 
-pub struct InitMessage {
-    receipt: Receipt,
-}
-
-#[derive(Debug)]
-pub struct TurnMessage {
-    shot: Position,
-}
-
-pub struct RoundMessage {
-    receipt: Receipt,
-}
-
-#[derive(Debug)]
-pub struct Battleship {
-    state: GameState,
-    last_shot: Position,
-    peer_state: Digest,
-}
-
-impl InitMessage {
-    pub fn get_state(&self) -> Result<Digest> {
-        let msg = self.receipt.get_journal_vec()?;
-        Ok(from_slice(msg.as_slice()).unwrap())
-    }
-}
-
-impl RoundMessage {
-    pub fn get_commit(&self) -> Result<RoundCommit> {
-        let msg = self.receipt.get_journal_vec()?;
-        Ok(from_slice(msg.as_slice()).unwrap())
-    }
-}
-
-impl Battleship {
-    pub fn new(state: GameState) -> Self {
-        Battleship {
-            state,
-            last_shot: Position::new(0, 0),
-            peer_state: Digest::default(),
-        }
-    }
-
-    pub fn init(&self) -> Result<InitMessage> {
-        let elf_contents = std::fs::read(INIT_PATH).unwrap();
-        let mut prover = Prover::new(&elf_contents, INIT_ID)?;
-        let vec = to_vec(&self.state).unwrap();
-        prover.add_input(vec.as_slice())?;
-        let receipt = prover.run()?;
-        Ok(InitMessage { receipt })
-    }
-
-    pub fn on_init_msg(&mut self, msg: &InitMessage) -> Result<()> {
-        log::info!("on_init_msg");
-        msg.receipt.verify(INIT_ID)?;
-        self.peer_state = msg.get_state()?;
-        log::info!("  peer_state: {:?}", self.peer_state);
-        Ok(())
-    }
-
-    pub fn turn(&mut self, x: u32, y: u32) -> TurnMessage {
-        let shot = Position::new(x, y);
-        log::info!("turn: {}", shot);
-        self.last_shot = shot.clone();
-        TurnMessage { shot: shot.clone() }
-    }
-
-    pub fn on_turn_msg(&mut self, msg: &TurnMessage) -> Result<RoundMessage> {
-        log::info!("on_turn_msg: {:?}", msg);
-        let params = RoundParams::new(self.state.clone(), msg.shot.x, msg.shot.y);
-
-        let elf_contents = std::fs::read(TURN_PATH).unwrap();
-        let mut prover = Prover::new(&elf_contents, TURN_ID)?;
-        let vec = to_vec(&params).unwrap();
-        prover.add_input(vec.as_slice())?;
-        let receipt = prover.run()?;
-        let vec = prover.get_output_vec()?;
-        let result = from_slice::<RoundResult>(vec.as_slice()).unwrap();
-        self.state = result.state.clone();
-        Ok(RoundMessage { receipt })
-    }
-
-    pub fn on_round_msg(&mut self, msg: &RoundMessage) -> Result<HitType> {
-        log::info!("on_round_msg");
-        msg.receipt.verify(TURN_ID)?;
-        let commit = msg.get_commit()?;
-        log::info!("  commit: {:?}", commit);
-
-        if commit.old_state != self.peer_state {
-            return Err(Exception::new(
-                format!(
-                    "Cheater: state mismatch. old_state ({}) != peer_state ({})",
-                    commit.old_state, self.peer_state
-                )
-                .as_str(),
-            ));
-        }
-
-        if commit.shot != self.last_shot {
-            return Err(Exception::new(
-                format!(
-                    "Cheater: shot mismatch. cur_shot ({}) != last_shot ({})",
-                    commit.shot, self.last_shot
-                )
-                .as_str(),
-            ));
-        }
-
-        self.peer_state = commit.new_state.clone();
-
-        Ok(commit.hit.clone())
-    }
-}
-
-fn round(player1: &mut Battleship, player2: &mut Battleship, x: u32, y: u32) -> HitType {
-    let turn = player1.turn(x, y);
-    player1
-        .on_round_msg(&player2.on_turn_msg(&turn).unwrap())
-        .unwrap()
-}
-
 #[ctor::ctor]
 fn init() {
     env_logger::builder().filter_level(LevelFilter::Info).init();
@@ -162,6 +42,9 @@ fn serde() {
         new_state: Digest::new([8, 7, 6, 5, 4, 3, 2, 1]),
         shot: Position::new(1, 9),
         hit: HitType::Hit,
+        fleet_destroyed: false,
+        seat: Seat::new("game", "alice"),
+        shots_taken: 3,
     };
     let buf: &mut [u32] = &mut [0; 256];
     let buf = to_slice(&commit, buf).unwrap();
@@ -171,70 +54,32 @@ fn serde() {
 }
 
 #[test]
-fn protocol() {
-    // Player Alice
-    //  | 0 1 2 3 4 5 6 7 8 9 |
-    // 0|                     |
-    // 1|       B B B B       |
-    // 2|                     |
-    // 3|     A               |
-    // 4|     A               |
-    // 5|     A         D D D |
-    // 6|     A               |
-    // 7|     A   C     E E   |
-    // 8|         C           |
-    // 9|         C           |
-    let alice_state = GameState {
-        ships: [
+fn reveal() {
+    let state = GameState {
+        rules: GameRules::default(),
+        ships: vec![
             Ship::new(2, 3, ShipDirection::Vertical),
             Ship::new(3, 1, ShipDirection::Horizontal),
             Ship::new(4, 7, ShipDirection::Vertical),
             Ship::new(7, 5, ShipDirection::Horizontal),
             Ship::new(7, 7, ShipDirection::Horizontal),
         ],
-        salt: 0xDEADBEEF,
-    };
-
-    // Player Bob
-    //  | 0 1 2 3 4 5 6 7 8 9 |
-    // 0| B B B B           A |
-    // 1| C                 A |
-    // 2| C                 A |
-    // 3| C                 A |
-    // 4| D                 A |
-    // 5| D                   |
-    // 6| D                   |
-    // 7| E                   |
-    // 8| E                   |
-    // 9|                     |
-    let bob_state = GameState {
-        ships: [
-            Ship::new(9, 0, ShipDirection::Vertical),
-            Ship::new(0, 0, ShipDirection::Horizontal),
-            Ship::new(0, 1, ShipDirection::Vertical),
-            Ship::new(0, 4, ShipDirection::Vertical),
-            Ship::new(0, 7, ShipDirection::Vertical),
-        ],
-        salt: 0xCAFECAFE,
+        salt: [0xDEADBEEF; 8],
+        shots: ShotMap::default(),
     };
-
-    let mut alice = Battleship::new(alice_state);
-    let mut bob = Battleship::new(bob_state);
-
-    let init = alice.init().unwrap();
-
-    bob.on_init_msg(&init).unwrap();
-    alice.on_init_msg(&bob.init().unwrap()).unwrap();
-
-    assert_eq!(round(&mut alice, &mut bob, 1, 1), HitType::Miss);
-    assert_eq!(round(&mut bob, &mut alice, 3, 1), HitType::Hit);
-
-    assert_eq!(round(&mut alice, &mut bob, 0, 0), HitType::Hit);
-    assert_eq!(round(&mut bob, &mut alice, 4, 1), HitType::Hit);
-
-    assert_eq!(round(&mut alice, &mut bob, 0, 1), HitType::Hit);
-    assert_eq!(round(&mut bob, &mut alice, 5, 1), HitType::Hit);
-
-    assert_eq!(round(&mut alice, &mut bob, 0, 2), HitType::Hit);
-    assert_eq!(round(&mut bob, &mut alice, 6, 1), HitType::Sunk(1));
+    let state = RoundParams::new(state, 3, 1, Seat::default())
+        .process()
+        .state;
+
+    let elf_contents = std::fs::read(REVEAL_PATH).unwrap();
+    let mut prover = Prover::new(&elf_contents, REVEAL_ID).unwrap();
+    let vec = to_vec(&state).unwrap();
+    prover.add_input(vec.as_slice()).unwrap();
+    let receipt = prover.run().unwrap();
+    receipt.verify(REVEAL_ID).unwrap();
+
+    let journal = receipt.get_journal_vec().unwrap();
+    let commit = from_slice::<RevealCommit>(journal.as_slice()).unwrap();
+    assert_eq!(commit.ships, state.ships);
+    assert_eq!(commit.ships[1].hit_mask, 0x01);
 }
@@ -17,14 +17,18 @@
 
 use risc0_zkvm_guest::{env, sha};
 
-use battleship_core::GameState;
+use battleship_core::{InitCommit, InitParams};
 
 risc0_zkvm_guest::entry!(main);
 
 pub fn main() {
-    let state: GameState = env::read();
-    if !state.check() {
+    let params: InitParams = env::read();
+    if !params.state.check() {
         panic!("Invalid GameState");
     }
-    env::commit(&sha::digest(&state));
+    env::commit(&InitCommit {
+        state: *sha::digest(&params.state),
+        rules: params.state.rules,
+        seat: params.seat,
+    });
 }
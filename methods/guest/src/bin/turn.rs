@@ -30,5 +30,8 @@ pub fn main() {
         new_state: *sha::digest(&result.state),
         shot: params.shot,
         hit: result.hit,
+        fleet_destroyed: result.fleet_destroyed,
+        seat: params.seat,
+        shots_taken: params.state.shots.len(),
     });
 }
@@ -0,0 +1,86 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, io};
+
+use battleship_protocol::VerifyError;
+use battleship_prover::ProverError;
+
+use crate::{CheatEvidence, Message};
+
+#[derive(Debug)]
+pub enum Error {
+    // Proving one of our own moves failed.
+    Prover(ProverError),
+    // A receipt from the peer is malformed or does not verify.
+    Verify(VerifyError),
+    // A receipt from the peer verifies but does not fit the game.
+    Receipt(&'static str),
+    // The peer set up their board under different rules.
+    RulesMismatch,
+    // The peer's board or round was proven for another seat than the one
+    // they took in this game.
+    SeatMismatch,
+    // The peer's round answers a shot other than the one we fired.
+    ShotMismatch,
+    // A valid receipt that contradicts what the peer committed to earlier.
+    Cheat(CheatEvidence),
+    // The peer sent a message we were not expecting at this point.
+    Unexpected(Message),
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Prover(err) => write!(f, "proving failed: {}", err),
+            Error::Verify(err) => write!(f, "bad receipt: {}", err),
+            Error::Receipt(msg) => write!(f, "bad receipt: {}", msg),
+            Error::RulesMismatch => write!(f, "peer uses different rules"),
+            Error::SeatMismatch => write!(f, "peer committed to another seat"),
+            Error::ShotMismatch => write!(f, "peer answered another shot"),
+            Error::Cheat(evidence) => write!(f, "cheater: {}", evidence),
+            Error::Unexpected(msg) => write!(f, "unexpected message: {:?}", msg),
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Json(err) => write!(f, "bad message: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ProverError> for Error {
+    fn from(err: ProverError) -> Self {
+        Error::Prover(err)
+    }
+}
+
+impl From<VerifyError> for Error {
+    fn from(err: VerifyError) -> Self {
+        Error::Verify(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
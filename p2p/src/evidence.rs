@@ -0,0 +1,96 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use battleship_core::{InitCommit, RoundCommit, Seat};
+use battleship_methods::{INIT_ID, TURN_ID};
+use battleship_protocol::{verify_receipt, ReceiptError, VerifyError};
+use risc0_zkvm_core::Digest;
+
+use crate::{Error, Result};
+
+/// What a round receipt contradicts.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Violation {
+    // The round starts from a board other than the one `prior`, the peer's
+    // init receipt or an earlier round receipt, left after as many shots
+    StateMismatch { prior: String },
+}
+
+/// Proof that the peer cheated: a round receipt that verifies, and the
+/// receipt it contradicts. Both were proven by the peer, so anyone holding
+/// this can check it with [CheatEvidence::verify] and award the game.
+///
+/// Only a board swap can be proven this way. A round answering the wrong
+/// shot or proven for another seat is refused with [Error::ShotMismatch] or
+/// [Error::SeatMismatch] instead, since which shot was fired and which seat
+/// the peer took are known to us alone.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CheatEvidence {
+    pub commit: RoundCommit,
+    pub violation: Violation,
+    pub receipt: String,
+}
+
+// The seat, shot count and board a receipt leaves its prover with.
+fn board_after(receipt: &str) -> Result<(Seat, u32, Digest)> {
+    match verify_receipt::<InitCommit>(receipt, INIT_ID) {
+        Ok(commit) => Ok((commit.seat, 0, commit.state)),
+        Err(VerifyError::Envelope(ReceiptError::WrongMethod)) => {
+            let commit: RoundCommit = verify_receipt(receipt, TURN_ID)?;
+            Ok((commit.seat.clone(), commit.shots_after(), commit.new_state))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+impl CheatEvidence {
+    /// Checks that both receipts verify, that the round commits to `commit`
+    /// and that it starts from another board than the one the prior receipt
+    /// left the same seat with after the same number of shots.
+    pub fn verify(&self) -> Result<()> {
+        let commit: RoundCommit = verify_receipt(&self.receipt, TURN_ID)?;
+        if commit != self.commit {
+            return Err(Error::Receipt("does not match the evidence"));
+        }
+        let violated = match &self.violation {
+            Violation::StateMismatch { prior } => {
+                let (seat, shots, state) = board_after(prior)?;
+                if seat != commit.seat || shots != commit.shots_taken {
+                    return Err(Error::Receipt("does not follow the prior receipt"));
+                }
+                commit.old_state != state
+            }
+        };
+        if !violated {
+            return Err(Error::Receipt("is consistent with the game"));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CheatEvidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.violation {
+            Violation::StateMismatch { .. } => write!(
+                f,
+                "state mismatch. old_state ({}) after {} shots differs from the prior receipt",
+                self.commit.old_state, self.commit.shots_taken
+            ),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Two players proving their moves to each other directly, without a
+//! contract in between. Each side runs a [Battleship] session, exchanges
+//! [Message]s over a [Transport] and checks every receipt it receives.
+
+mod error;
+mod evidence;
+mod message;
+mod session;
+mod transport;
+
+pub use error::Error;
+pub use evidence::{CheatEvidence, Violation};
+pub use message::Message;
+pub use session::Battleship;
+pub use transport::{TcpTransport, Transport};
+
+pub type Result<T> = std::result::Result<T, Error>;
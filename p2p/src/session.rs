@@ -0,0 +1,161 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use battleship_core::{
+    GameState, InitCommit, InitParams, Position, RoundCommit, RoundParams, RoundResult, Seat,
+};
+use battleship_methods::{INIT_ID, TURN_ID};
+use battleship_protocol::verify_receipt;
+use battleship_prover::{LocalProver, ProverBackend};
+use risc0_zkvm_core::Digest;
+
+use crate::{CheatEvidence, Error, Message, Result, Violation};
+
+/// One player's side of a game: our secret fleet, plus what the peer has
+/// committed to so far.
+pub struct Battleship {
+    prover: Arc<LocalProver>,
+    seat: Seat,
+    state: GameState,
+    last_shot: Position,
+    peer_seat: Seat,
+    peer_state: Digest,
+    peer_shots: u32,
+    // The receipt that left the peer with `peer_state`, kept as evidence
+    peer_receipt: String,
+}
+
+impl Battleship {
+    /// Starts our side of the game named by `seat`, which every receipt we
+    /// prove commits to. The prover may be shared between sessions.
+    pub fn new(prover: Arc<LocalProver>, seat: Seat, state: GameState) -> Self {
+        Battleship {
+            prover,
+            seat,
+            state,
+            last_shot: Position::new(0, 0),
+            peer_seat: Seat::default(),
+            peer_state: Digest::default(),
+            peer_shots: 0,
+            peer_receipt: String::new(),
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn init(&self) -> Result<Message> {
+        let params = InitParams::new(self.state.clone(), self.seat.clone());
+        Ok(Message::Init {
+            receipt: self.prover.prove_init(&params)?,
+        })
+    }
+
+    /// Reads the peer's init commitment, so a player joining a game can learn
+    /// its name and rules before proving their own board.
+    pub fn peer_init(msg: &Message) -> Result<InitCommit> {
+        match msg {
+            Message::Init { receipt } => Ok(verify_receipt(receipt, INIT_ID)?),
+            _ => Err(Error::Unexpected(msg.clone())),
+        }
+    }
+
+    pub fn on_init_msg(&mut self, msg: &Message) -> Result<()> {
+        log::info!("on_init_msg");
+        let receipt = match msg {
+            Message::Init { receipt } => receipt,
+            _ => return Err(Error::Unexpected(msg.clone())),
+        };
+        let commit: InitCommit = verify_receipt(receipt, INIT_ID)?;
+        if commit.rules != self.state.rules {
+            return Err(Error::RulesMismatch);
+        }
+        // The peer must take the other seat in this game
+        if commit.seat.game != self.seat.game || commit.seat.player == self.seat.player {
+            return Err(Error::SeatMismatch);
+        }
+        self.peer_seat = commit.seat;
+        self.peer_state = commit.state;
+        self.peer_shots = 0;
+        self.peer_receipt = receipt.clone();
+        log::info!("  peer_state: {:?}", self.peer_state);
+        Ok(())
+    }
+
+    pub fn turn(&mut self, x: u32, y: u32) -> Message {
+        let shot = Position::new(x, y);
+        log::info!("turn: {}", shot);
+        self.last_shot = shot.clone();
+        Message::Turn { shot }
+    }
+
+    /// Proves the outcome of the peer's shot against our fleet.
+    pub fn on_turn_msg(&mut self, msg: &Message) -> Result<(RoundResult, Message)> {
+        log::info!("on_turn_msg: {:?}", msg);
+        let shot = match msg {
+            Message::Turn { shot } => shot,
+            _ => return Err(Error::Unexpected(msg.clone())),
+        };
+        // Shots off the board or at a cell already fired at are answered with
+        // a proven HitType::InvalidShot, costing the peer their shot
+        let params = RoundParams::new(self.state.clone(), shot.x, shot.y, self.seat.clone());
+        let turn = self.prover.prove_turn(&params)?;
+        self.state = turn.state.state.clone();
+        let msg = Message::Round {
+            receipt: turn.receipt,
+        };
+        Ok((turn.state, msg))
+    }
+
+    /// Checks the peer's answer to our last shot.
+    pub fn on_round_msg(&mut self, msg: &Message) -> Result<RoundCommit> {
+        log::info!("on_round_msg");
+        let receipt = match msg {
+            Message::Round { receipt } => receipt,
+            _ => return Err(Error::Unexpected(msg.clone())),
+        };
+        let commit: RoundCommit = verify_receipt(receipt, TURN_ID)?;
+        log::info!("  commit: {:?}", commit);
+
+        if commit.seat != self.peer_seat {
+            return Err(Error::SeatMismatch);
+        }
+        // A replayed or skipped round is refused, but proves nothing: the
+        // peer may well have proven it honestly
+        if commit.shots_taken != self.peer_shots {
+            return Err(Error::Receipt("out of order"));
+        }
+        if commit.old_state != self.peer_state {
+            return Err(Error::Cheat(CheatEvidence {
+                commit,
+                violation: Violation::StateMismatch {
+                    prior: self.peer_receipt.clone(),
+                },
+                receipt: receipt.clone(),
+            }));
+        }
+        if commit.shot != self.last_shot {
+            return Err(Error::ShotMismatch);
+        }
+
+        self.peer_state = commit.new_state;
+        self.peer_shots = commit.shots_after();
+        self.peer_receipt = receipt.clone();
+
+        Ok(commit)
+    }
+}
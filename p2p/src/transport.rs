@@ -0,0 +1,128 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crate::{Message, Result};
+
+// Receipts make up most of a message; a longer line is never an honest one.
+const MAX_MESSAGE_LEN: u64 = 4 << 20;
+
+/// Carries messages between the two players, in order.
+pub trait Transport {
+    fn send(&mut self, msg: &Message) -> Result<()>;
+    fn recv(&mut self) -> Result<Message>;
+}
+
+/// Newline-delimited JSON over a TCP connection.
+pub struct TcpTransport {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpTransport {
+    pub fn new(stream: TcpStream) -> Result<Self> {
+        Ok(TcpTransport {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Waits for the opponent to connect.
+    pub fn listen(addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, peer) = listener.accept()?;
+        log::info!("peer connected from {}", peer);
+        Self::new(stream)
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, msg: &Message) -> Result<()> {
+        let mut line = serde_json::to_vec(msg)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Message> {
+        let mut line = Vec::new();
+        (&mut self.reader)
+            .take(MAX_MESSAGE_LEN)
+            .read_until(b'\n', &mut line)?;
+        if line.last() != Some(&b'\n') {
+            if line.len() as u64 == MAX_MESSAGE_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long").into());
+            }
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(serde_json::from_slice(&line)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use battleship_core::Position;
+
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let peer = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut transport = TcpTransport::new(stream).unwrap();
+            let msg = transport.recv().unwrap();
+            transport.send(&msg).unwrap();
+        });
+
+        let mut transport = TcpTransport::connect(addr).unwrap();
+        let msg = Message::Turn {
+            shot: Position::new(3, 4),
+        };
+        transport.send(&msg).unwrap();
+        assert_eq!(transport.recv().unwrap(), msg);
+        peer.join().unwrap();
+        assert!(transport.recv().is_err());
+    }
+
+    #[test]
+    fn too_long() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let peer = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Ignore the error if the reader hangs up first
+            let _ = stream.write_all(&vec![b' '; MAX_MESSAGE_LEN as usize + 1]);
+        });
+
+        let mut transport = TcpTransport::connect(addr).unwrap();
+        match transport.recv() {
+            Err(Error::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+            other => panic!("unexpected {:?}", other),
+        }
+        peer.join().unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use battleship_core::{GameRules, GameState, HitType, Seat, Ship, ShipDirection, ShotMap};
+use battleship_p2p::{Battleship, CheatEvidence, Error, Message, Violation};
+use battleship_prover::LocalProver;
+use log::LevelFilter;
+
+fn round(player1: &mut Battleship, player2: &mut Battleship, x: u32, y: u32) -> HitType {
+    let turn = player1.turn(x, y);
+    let (_, msg) = player2.on_turn_msg(&turn).unwrap();
+    player1.on_round_msg(&msg).unwrap().hit
+}
+
+#[ctor::ctor]
+fn init() {
+    env_logger::builder().filter_level(LevelFilter::Info).init();
+}
+
+#[test]
+fn protocol() {
+    // Player Alice
+    //  | 0 1 2 3 4 5 6 7 8 9 |
+    // 0|                     |
+    // 1|       B B B B       |
+    // 2|                     |
+    // 3|     A               |
+    // 4|     A               |
+    // 5|     A         D D D |
+    // 6|     A               |
+    // 7|     A   C     E E   |
+    // 8|         C           |
+    // 9|         C           |
+    let alice_state = GameState {
+        rules: GameRules::default(),
+        ships: vec![
+            Ship::new(2, 3, ShipDirection::Vertical),
+            Ship::new(3, 1, ShipDirection::Horizontal),
+            Ship::new(4, 7, ShipDirection::Vertical),
+            Ship::new(7, 5, ShipDirection::Horizontal),
+            Ship::new(7, 7, ShipDirection::Horizontal),
+        ],
+        salt: [0xDEADBEEF; 8],
+        shots: ShotMap::default(),
+    };
+
+    // Player Bob
+    //  | 0 1 2 3 4 5 6 7 8 9 |
+    // 0| B B B B           A |
+    // 1| C                 A |
+    // 2| C                 A |
+    // 3| C                 A |
+    // 4| D                 A |
+    // 5| D                   |
+    // 6| D                   |
+    // 7| E                   |
+    // 8| E                   |
+    // 9|                     |
+    let bob_state = GameState {
+        rules: GameRules::default(),
+        ships: vec![
+            Ship::new(9, 0, ShipDirection::Vertical),
+            Ship::new(0, 0, ShipDirection::Horizontal),
+            Ship::new(0, 1, ShipDirection::Vertical),
+            Ship::new(0, 4, ShipDirection::Vertical),
+            Ship::new(0, 7, ShipDirection::Vertical),
+        ],
+        salt: [0xCAFECAFE; 8],
+        shots: ShotMap::default(),
+    };
+
+    let prover = Arc::new(LocalProver::load().unwrap());
+    let mut alice = Battleship::new(prover.clone(), Seat::new("duel", "alice"), alice_state);
+    let mut bob = Battleship::new(prover.clone(), Seat::new("duel", "bob"), bob_state);
+
+    let init = alice.init().unwrap();
+
+    // Alice's own board cannot be played back to her as her opponent's
+    assert!(matches!(alice.on_init_msg(&init), Err(Error::SeatMismatch)));
+    bob.on_init_msg(&init).unwrap();
+    let bob_init = bob.init().unwrap();
+    alice.on_init_msg(&bob_init).unwrap();
+    let bob_init = match bob_init {
+        Message::Init { receipt } => receipt,
+        other => panic!("expected an init message, got {:?}", other),
+    };
+
+    // Answering from another board is caught, with evidence a third party
+    // can check against Bob's init receipt
+    let mut swapped = bob.state().clone();
+    swapped.salt = [0xBADBAD; 8];
+    let mut swapped = Battleship::new(prover, Seat::new("duel", "bob"), swapped);
+    let turn = alice.turn(1, 1);
+    let (_, msg) = swapped.on_turn_msg(&turn).unwrap();
+    match alice.on_round_msg(&msg) {
+        Err(Error::Cheat(evidence)) => {
+            assert_eq!(
+                evidence.violation,
+                Violation::StateMismatch {
+                    prior: bob_init.clone()
+                }
+            );
+            evidence.verify().unwrap();
+        }
+        other => panic!("expected cheat evidence, got {:?}", other),
+    }
+
+    assert_eq!(round(&mut alice, &mut bob, 1, 1), HitType::Miss);
+    assert_eq!(round(&mut bob, &mut alice, 3, 1), HitType::Hit);
+
+    assert_eq!(round(&mut alice, &mut bob, 0, 0), HitType::Hit);
+    assert_eq!(round(&mut bob, &mut alice, 4, 1), HitType::Hit);
+
+    assert_eq!(round(&mut alice, &mut bob, 0, 1), HitType::Hit);
+    assert_eq!(round(&mut bob, &mut alice, 5, 1), HitType::Hit);
+
+    assert_eq!(round(&mut alice, &mut bob, 0, 2), HitType::Hit);
+    assert_eq!(round(&mut bob, &mut alice, 6, 1), HitType::Sunk(1));
+
+    // Replaying an old answer is caught, but proves nothing
+    let turn = alice.turn(9, 9);
+    let (_, prior) = bob.on_turn_msg(&turn).unwrap();
+    alice.on_round_msg(&prior).unwrap();
+    let turn = alice.turn(9, 8);
+    let (_, msg) = bob.on_turn_msg(&turn).unwrap();
+    let commit = alice.on_round_msg(&msg).unwrap();
+    alice.turn(9, 7);
+    assert!(matches!(alice.on_round_msg(&msg), Err(Error::Receipt(_))));
+
+    // Honest rounds cannot be passed off as evidence, whichever receipt of
+    // Bob's they are paired with
+    let receipt = |msg: Message| match msg {
+        Message::Round { receipt } => receipt,
+        other => panic!("expected a round message, got {:?}", other),
+    };
+    for prior in [bob_init, receipt(prior)] {
+        let forged = CheatEvidence {
+            commit: commit.clone(),
+            violation: Violation::StateMismatch { prior },
+            receipt: receipt(msg.clone()),
+        };
+        assert!(matches!(forged.verify(), Err(Error::Receipt(_))));
+    }
+}
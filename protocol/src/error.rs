@@ -0,0 +1,128 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BattleshipError {
+    GameNotFound,
+    GameExists,
+    WrongTurn,
+    NotYourTurn,
+    NotAllowed,
+    DeadlineNotPassed,
+    BadReceipt,
+    StateMismatch,
+    ShotMismatch,
+    RulesMismatch,
+    UnsupportedReceipt,
+    WrongMethod,
+    ShotOutOfBounds,
+    DuplicateShot,
+    WrongSeat,
+    SelfJoin,
+    NotInvited,
+    InvalidTimeout,
+}
+
+impl BattleshipError {
+    pub const ALL: [BattleshipError; 18] = [
+        BattleshipError::GameNotFound,
+        BattleshipError::GameExists,
+        BattleshipError::WrongTurn,
+        BattleshipError::NotYourTurn,
+        BattleshipError::NotAllowed,
+        BattleshipError::DeadlineNotPassed,
+        BattleshipError::BadReceipt,
+        BattleshipError::StateMismatch,
+        BattleshipError::ShotMismatch,
+        BattleshipError::RulesMismatch,
+        BattleshipError::UnsupportedReceipt,
+        BattleshipError::WrongMethod,
+        BattleshipError::ShotOutOfBounds,
+        BattleshipError::DuplicateShot,
+        BattleshipError::WrongSeat,
+        BattleshipError::SelfJoin,
+        BattleshipError::NotInvited,
+        BattleshipError::InvalidTimeout,
+    ];
+
+    // Clients match on these codes, so they must never change
+    pub fn code(&self) -> &'static str {
+        match self {
+            BattleshipError::GameNotFound => "ERR_GAME_NOT_FOUND",
+            BattleshipError::GameExists => "ERR_GAME_EXISTS",
+            BattleshipError::WrongTurn => "ERR_WRONG_TURN",
+            BattleshipError::NotYourTurn => "ERR_NOT_YOUR_TURN",
+            BattleshipError::NotAllowed => "ERR_NOT_ALLOWED",
+            BattleshipError::DeadlineNotPassed => "ERR_DEADLINE_NOT_PASSED",
+            BattleshipError::BadReceipt => "ERR_BAD_RECEIPT",
+            BattleshipError::StateMismatch => "ERR_STATE_MISMATCH",
+            BattleshipError::ShotMismatch => "ERR_SHOT_MISMATCH",
+            BattleshipError::RulesMismatch => "ERR_RULES_MISMATCH",
+            BattleshipError::UnsupportedReceipt => "ERR_UNSUPPORTED_RECEIPT",
+            BattleshipError::WrongMethod => "ERR_WRONG_METHOD",
+            BattleshipError::ShotOutOfBounds => "ERR_SHOT_OUT_OF_BOUNDS",
+            BattleshipError::DuplicateShot => "ERR_DUPLICATE_SHOT",
+            BattleshipError::WrongSeat => "ERR_WRONG_SEAT",
+            BattleshipError::SelfJoin => "ERR_SELF_JOIN",
+            BattleshipError::NotInvited => "ERR_NOT_INVITED",
+            BattleshipError::InvalidTimeout => "ERR_INVALID_TIMEOUT",
+        }
+    }
+
+    // Picks the error code out of a contract panic message, which may wrap it
+    // in other text
+    pub fn from_message(msg: &str) -> Option<Self> {
+        BattleshipError::ALL
+            .into_iter()
+            .find(|err| msg.contains(err.code()))
+    }
+}
+
+impl fmt::Display for BattleshipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            BattleshipError::GameNotFound => "game not found",
+            BattleshipError::GameExists => "game already exists",
+            BattleshipError::WrongTurn => "game is not expecting this move",
+            BattleshipError::NotYourTurn => "caller is not the player expected to move",
+            BattleshipError::NotAllowed => "caller is not allowed to do this",
+            BattleshipError::DeadlineNotPassed => "turn deadline has not passed",
+            BattleshipError::BadReceipt => "receipt failed to decode or verify",
+            BattleshipError::StateMismatch => "receipt does not match the committed board",
+            BattleshipError::ShotMismatch => "receipt does not answer the last shot",
+            BattleshipError::RulesMismatch => "board was set up under different rules",
+            BattleshipError::UnsupportedReceipt => "receipt format version is not supported",
+            BattleshipError::WrongMethod => "receipt is for a different method",
+            BattleshipError::ShotOutOfBounds => "shot is off the board",
+            BattleshipError::DuplicateShot => "shot at a cell already fired at",
+            BattleshipError::WrongSeat => "receipt was proven for another game or player",
+            BattleshipError::SelfJoin => "players cannot play against themselves",
+            BattleshipError::NotInvited => "game is reserved for another player",
+            BattleshipError::InvalidTimeout => "turn timeout is out of range",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl std::error::Error for BattleshipError {}
+
+pub fn require(cond: bool, err: BattleshipError) -> Result<(), BattleshipError> {
+    if cond {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
@@ -0,0 +1,769 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The rules of a battleship match between two players, independent of where
+//! the game is hosted. A host (the NEAR contract, the local coordinator, a
+//! test) verifies receipts, identifies the caller and tells the time; the
+//! [Referee] decides whether a move is legal and updates the [GameState].
+
+mod error;
+mod receipt;
+
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use battleship_core::{
+    GameRules, HitType, InitCommit, Position, RevealCommit, RoundCommit, Seat, Ship, ShipDirection,
+};
+use risc0_zkvm_core::Digest;
+
+pub use error::{require, BattleshipError};
+#[cfg(feature = "verify")]
+pub use receipt::{decode_receipt, verify_receipt, VerifyError};
+pub use receipt::{ReceiptEnvelope, ReceiptError, WireReceipt, RECEIPT_VERSION};
+
+pub const GAME_OVER: u32 = 3;
+pub const DEFAULT_TURN_TIMEOUT_SECS: u64 = 60 * 60;
+// Bounds on a game's turn timeout: long enough that a forfeit cannot be
+// claimed straight after the opponent moves, short enough to end abandoned games
+pub const MIN_TURN_TIMEOUT_SECS: u64 = 60;
+pub const MAX_TURN_TIMEOUT_SECS: u64 = 7 * 24 * 60 * 60;
+
+pub type PlayerId = String;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
+pub struct Rules {
+    pub width: u32,
+    pub height: u32,
+    pub spans: Vec<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
+pub struct RevealedShip {
+    pub x: u32,
+    pub y: u32,
+    pub vertical: bool,
+    pub hit_mask: u8,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
+pub struct PlayerState {
+    pub id: PlayerId,
+    pub board: [u32; 8],
+    pub shot_x: u32,
+    pub shot_y: u32,
+    pub shots: Vec<u32>, // Cells this player has fired at, as y * width + x
+    pub revealed: Option<Vec<RevealedShip>>, // Set once the player's fleet is revealed
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
+pub struct GameState {
+    // 0 means p1 has setup game, and p2 needs to do setup
+    // 1 means p1 needs to process p2's shot and make it's own
+    // 2 means p2 needs to process p1's shot and make it's own
+    // 3 means the game is over, see `winner`
+    pub next_turn: u32,
+    pub rules: Rules, // Board size and fleet both players committed to
+    pub p1: PlayerState,
+    pub p2: PlayerState,
+    pub last_hit: u8,  // 0 = miss, 1 = hit, 2 = sunk, 3 = invalid shot
+    pub sunk_what: u8, // Which ship was sunk
+    pub winner: Option<PlayerId>,
+    pub last_move_ms: u64,          // Host timestamp of the last move
+    pub turn_timeout_ms: u64,       // How long a player may take before forfeiting
+    pub opponent: Option<PlayerId>, // The only player who may join, if set
+}
+
+impl From<GameRules> for Rules {
+    fn from(rules: GameRules) -> Self {
+        Rules {
+            width: rules.width,
+            height: rules.height,
+            spans: rules.spans,
+        }
+    }
+}
+
+impl From<&Ship> for RevealedShip {
+    fn from(ship: &Ship) -> Self {
+        RevealedShip {
+            x: ship.pos.x,
+            y: ship.pos.y,
+            vertical: ship.dir == ShipDirection::Vertical,
+            hit_mask: ship.hit_mask,
+        }
+    }
+}
+
+fn board(digest: &Digest) -> [u32; 8] {
+    digest.as_slice().try_into().unwrap()
+}
+
+impl PlayerState {
+    // Shots off the board or at a cell already fired at are rejected here:
+    // the defender's turn guest could never answer them, stalling the game
+    fn check_shot(&self, rules: &Rules, shot: &Position) -> Result<u32, BattleshipError> {
+        require(
+            shot.x < rules.width && shot.y < rules.height,
+            BattleshipError::ShotOutOfBounds,
+        )?;
+        let cell = shot.y * rules.width + shot.x;
+        require(!self.shots.contains(&cell), BattleshipError::DuplicateShot)?;
+        Ok(cell)
+    }
+}
+
+impl GameState {
+    pub fn is_participant(&self, id: &str) -> bool {
+        self.p1.id == id || self.p2.id == id
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.next_turn == GAME_OVER
+    }
+
+    pub fn deadline_passed(&self, now_ms: u64) -> bool {
+        now_ms > self.last_move_ms.saturating_add(self.turn_timeout_ms)
+    }
+
+    fn is_unjoined(&self) -> bool {
+        self.next_turn == 0 && self.p2.id.is_empty()
+    }
+
+    fn in_play(&self) -> bool {
+        self.next_turn == 1 || self.next_turn == 2
+    }
+}
+
+/// Checks moves against a [GameState] and applies them. Every check happens
+/// before the state is touched, so a rejected move leaves the game as it was.
+pub struct Referee {
+    now_ms: u64,
+}
+
+impl Referee {
+    pub fn new(now_ms: u64) -> Self {
+        Referee { now_ms }
+    }
+
+    // Set's p1's initial state
+    pub fn new_game(
+        &self,
+        name: &str,
+        p1: &str,
+        commit: InitCommit,
+        opponent: Option<PlayerId>,
+        turn_timeout_secs: Option<u64>,
+    ) -> Result<GameState, BattleshipError> {
+        // Verify the board was committed for this seat
+        require(
+            commit.seat == Seat::new(name, p1),
+            BattleshipError::WrongSeat,
+        )?;
+        // Verify p1 is not inviting themselves
+        require(opponent.as_deref() != Some(p1), BattleshipError::SelfJoin)?;
+        // Verify the turn timeout is within bounds
+        let turn_timeout_secs = turn_timeout_secs.unwrap_or(DEFAULT_TURN_TIMEOUT_SECS);
+        require(
+            (MIN_TURN_TIMEOUT_SECS..=MAX_TURN_TIMEOUT_SECS).contains(&turn_timeout_secs),
+            BattleshipError::InvalidTimeout,
+        )?;
+        Ok(GameState {
+            next_turn: 0,
+            rules: commit.rules.into(),
+            p1: PlayerState {
+                id: p1.to_string(),
+                board: board(&commit.state),
+                ..Default::default()
+            },
+            p2: PlayerState::default(),
+            last_hit: 0,
+            sunk_what: 0,
+            winner: None,
+            last_move_ms: self.now_ms,
+            turn_timeout_ms: turn_timeout_secs * 1000,
+            opponent,
+        })
+    }
+
+    // Set's p2's state, and makes the first shot at p1
+    pub fn join_game(
+        &self,
+        state: &mut GameState,
+        name: &str,
+        p2: &str,
+        commit: InitCommit,
+        shot: Position,
+    ) -> Result<(), BattleshipError> {
+        // Verify we are are on turn 0
+        require(state.next_turn == 0, BattleshipError::WrongTurn)?;
+        // Verify p1 is not taking both seats
+        require(state.p1.id != p2, BattleshipError::SelfJoin)?;
+        // Verify the game is open to this player
+        require(
+            state
+                .opponent
+                .as_deref()
+                .map_or(true, |opponent| opponent == p2),
+            BattleshipError::NotInvited,
+        )?;
+        // Verify the board was committed for this seat
+        require(
+            commit.seat == Seat::new(name, p2),
+            BattleshipError::WrongSeat,
+        )?;
+        // Verify the player set up their board under the same rules
+        require(
+            Rules::from(commit.rules) == state.rules,
+            BattleshipError::RulesMismatch,
+        )?;
+        let mut player = PlayerState {
+            id: p2.to_string(),
+            board: board(&commit.state),
+            shot_x: shot.x,
+            shot_y: shot.y,
+            ..Default::default()
+        };
+        let cell = player.check_shot(&state.rules, &shot)?;
+        player.shots.push(cell);
+        state.next_turn = 1;
+        state.p2 = player;
+        state.last_move_ms = self.now_ms;
+        Ok(())
+    }
+
+    // Answer the prior shot and make a new one
+    pub fn turn(
+        &self,
+        state: &mut GameState,
+        name: &str,
+        player: &str,
+        commit: RoundCommit,
+        shot: Position,
+    ) -> Result<(), BattleshipError> {
+        // Verify we are are on turn 1 or 2 (and the game isn't over)
+        require(state.in_play(), BattleshipError::WrongTurn)?;
+        let (cur_player, prev_player) = if state.next_turn == 1 {
+            (&mut state.p1, &mut state.p2)
+        } else {
+            (&mut state.p2, &mut state.p1)
+        };
+        // Verify the right player is playing
+        require(cur_player.id == player, BattleshipError::NotYourTurn)?;
+        // Verify the answer was proven for this seat
+        require(
+            commit.seat == Seat::new(name, player),
+            BattleshipError::WrongSeat,
+        )?;
+        // Make sure the prior state matches the current state
+        require(
+            cur_player.board == board(&commit.old_state),
+            BattleshipError::StateMismatch,
+        )?;
+        // Make sure the response matches the prior shot
+        require(
+            commit.shot == Position::new(prev_player.shot_x, prev_player.shot_y),
+            BattleshipError::ShotMismatch,
+        )?;
+        // If the current player just lost their last ship, the game is over
+        // and their new shot goes unused
+        let winner = commit.fleet_destroyed.then(|| prev_player.id.clone());
+        let cell = match winner {
+            Some(_) => None,
+            None => Some(cur_player.check_shot(&state.rules, &shot)?),
+        };
+        // Update the current players state
+        cur_player.board = board(&commit.new_state);
+        if let Some(cell) = cell {
+            cur_player.shot_x = shot.x;
+            cur_player.shot_y = shot.y;
+            cur_player.shots.push(cell);
+        }
+        // Set the hit status
+        match commit.hit {
+            HitType::Miss => state.last_hit = 0,
+            HitType::Hit => state.last_hit = 1,
+            HitType::Sunk(ship) => {
+                state.last_hit = 2;
+                state.sunk_what = ship;
+            }
+            // The shooter forfeits the shot, play passes back as usual
+            HitType::InvalidShot => state.last_hit = 3,
+        }
+        state.last_move_ms = self.now_ms;
+        if winner.is_some() {
+            state.winner = winner;
+            state.next_turn = GAME_OVER;
+        } else {
+            state.next_turn = 3 - state.next_turn;
+        }
+        Ok(())
+    }
+
+    // End the game in the caller's favour if their opponent missed the turn deadline
+    pub fn claim_forfeit(
+        &self,
+        state: &mut GameState,
+        player: &str,
+    ) -> Result<(), BattleshipError> {
+        // Verify we are are on turn 1 or 2 (and the game isn't over)
+        require(state.in_play(), BattleshipError::WrongTurn)?;
+        // Verify the caller is the player waiting on their opponent
+        let waiting_player = if state.next_turn == 1 {
+            &state.p2
+        } else {
+            &state.p1
+        };
+        require(waiting_player.id == player, BattleshipError::NotAllowed)?;
+        // Verify the opponent let the deadline pass
+        require(
+            state.deadline_passed(self.now_ms),
+            BattleshipError::DeadlineNotPassed,
+        )?;
+        state.winner = Some(waiting_player.id.clone());
+        state.next_turn = GAME_OVER;
+        Ok(())
+    }
+
+    // Publish a player's final fleet once the game is over
+    pub fn reveal(
+        &self,
+        state: &mut GameState,
+        commit: RevealCommit,
+    ) -> Result<(), BattleshipError> {
+        require(state.is_over(), BattleshipError::WrongTurn)?;
+        // The revealed fleet must belong to one of the players' final boards
+        let revealed = board(&commit.state);
+        let player = if state.p1.board == revealed {
+            &mut state.p1
+        } else if state.p2.board == revealed {
+            &mut state.p2
+        } else {
+            return Err(BattleshipError::StateMismatch);
+        };
+        player.revealed = Some(commit.ships.iter().map(RevealedShip::from).collect());
+        Ok(())
+    }
+
+    // Participants may withdraw a game nobody has joined yet. Anyone may clean
+    // up a finished game or an open one left unjoined past its deadline, but a
+    // game in play is settled by forfeit rather than deleted
+    pub fn may_delete(&self, state: &GameState, caller: &str) -> bool {
+        let unjoined = state.is_unjoined();
+        (unjoined && state.is_participant(caller))
+            || state.is_over()
+            || (unjoined && state.deadline_passed(self.now_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(n: u32) -> Digest {
+        Digest::new([n; 8])
+    }
+
+    const GAME: &str = "game";
+
+    fn init(n: u32, player: &str) -> InitCommit {
+        InitCommit {
+            state: digest(n),
+            rules: GameRules::default(),
+            seat: Seat::new(GAME, player),
+        }
+    }
+
+    fn round(player: &str, old: u32, new: u32, x: u32, y: u32, hit: HitType) -> RoundCommit {
+        RoundCommit {
+            old_state: digest(old),
+            new_state: digest(new),
+            shot: Position::new(x, y),
+            hit,
+            fleet_destroyed: false,
+            seat: Seat::new(GAME, player),
+            shots_taken: 0,
+        }
+    }
+
+    // alice created the game with board 10, bob joined with board 20 and
+    // fired at (1, 1)
+    fn started() -> GameState {
+        let referee = Referee::new(0);
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, None)
+            .unwrap();
+        referee
+            .join_game(
+                &mut state,
+                GAME,
+                "bob",
+                init(20, "bob"),
+                Position::new(1, 1),
+            )
+            .unwrap();
+        state
+    }
+
+    #[test]
+    fn game() {
+        let referee = Referee::new(1000);
+        let mut state = started();
+        assert_eq!(state.next_turn, 1);
+
+        let commit = round("alice", 10, 11, 1, 1, HitType::Miss);
+        referee
+            .turn(&mut state, GAME, "alice", commit, Position::new(2, 2))
+            .unwrap();
+        assert_eq!(state.next_turn, 2);
+        assert_eq!(state.last_hit, 0);
+        assert_eq!(state.p1.board, [11; 8]);
+        assert_eq!((state.p1.shot_x, state.p1.shot_y), (2, 2));
+        assert_eq!(state.last_move_ms, 1000);
+
+        let commit = round("bob", 20, 20, 2, 2, HitType::InvalidShot);
+        referee
+            .turn(&mut state, GAME, "bob", commit, Position::new(2, 3))
+            .unwrap();
+        assert_eq!((state.next_turn, state.last_hit), (1, 3));
+        let commit = round("alice", 11, 11, 2, 3, HitType::Miss);
+        referee
+            .turn(&mut state, GAME, "alice", commit, Position::new(5, 5))
+            .unwrap();
+
+        let commit = round("bob", 20, 21, 5, 5, HitType::Sunk(4));
+        referee
+            .turn(&mut state, GAME, "bob", commit, Position::new(3, 3))
+            .unwrap();
+        assert_eq!(state.next_turn, 1);
+        assert_eq!((state.last_hit, state.sunk_what), (2, 4));
+
+        let mut commit = round("alice", 11, 12, 3, 3, HitType::Sunk(0));
+        commit.fleet_destroyed = true;
+        referee
+            .turn(&mut state, GAME, "alice", commit, Position::new(4, 4))
+            .unwrap();
+        assert!(state.is_over());
+        assert_eq!(state.winner.as_deref(), Some("bob"));
+        assert_eq!((state.p1.shot_x, state.p1.shot_y), (5, 5));
+
+        let commit = round("bob", 21, 22, 4, 4, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "bob", commit, Position::new(0, 0)),
+            Err(BattleshipError::WrongTurn)
+        );
+    }
+
+    #[test]
+    fn join() {
+        let referee = Referee::new(0);
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, Some(60))
+            .unwrap();
+        assert_eq!(state.turn_timeout_ms, 60_000);
+        for timeout in [
+            0,
+            MIN_TURN_TIMEOUT_SECS - 1,
+            MAX_TURN_TIMEOUT_SECS + 1,
+            u64::MAX,
+        ] {
+            assert_eq!(
+                referee.new_game(GAME, "alice", init(10, "alice"), None, Some(timeout)),
+                Err(BattleshipError::InvalidTimeout)
+            );
+        }
+
+        let mut commit = init(20, "bob");
+        commit.rules = GameRules::new(8, 8, vec![4, 3, 3, 2]);
+        assert_eq!(
+            referee.join_game(&mut state, GAME, "bob", commit, Position::new(1, 1)),
+            Err(BattleshipError::RulesMismatch)
+        );
+        assert_eq!(state.next_turn, 0);
+
+        let mut state = started();
+        assert_eq!(
+            referee.join_game(
+                &mut state,
+                GAME,
+                "carol",
+                init(30, "carol"),
+                Position::new(1, 1)
+            ),
+            Err(BattleshipError::WrongTurn)
+        );
+    }
+
+    #[test]
+    fn invites() {
+        let referee = Referee::new(0);
+        assert_eq!(
+            referee.new_game(GAME, "alice", init(10, "alice"), Some("alice".into()), None),
+            Err(BattleshipError::SelfJoin)
+        );
+
+        // alice cannot take both seats of an open game
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, None)
+            .unwrap();
+        assert_eq!(
+            referee.join_game(
+                &mut state,
+                GAME,
+                "alice",
+                init(20, "alice"),
+                Position::new(1, 1)
+            ),
+            Err(BattleshipError::SelfJoin)
+        );
+        assert_eq!(state.next_turn, 0);
+
+        // only bob may join a game reserved for him
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), Some("bob".into()), None)
+            .unwrap();
+        assert_eq!(
+            referee.join_game(
+                &mut state,
+                GAME,
+                "carol",
+                init(30, "carol"),
+                Position::new(1, 1)
+            ),
+            Err(BattleshipError::NotInvited)
+        );
+        assert_eq!(state.next_turn, 0);
+        referee
+            .join_game(
+                &mut state,
+                GAME,
+                "bob",
+                init(20, "bob"),
+                Position::new(1, 1),
+            )
+            .unwrap();
+        assert_eq!(state.p2.id, "bob");
+    }
+
+    #[test]
+    fn seats() {
+        let referee = Referee::new(0);
+        // alice's board from another game, or from bob's seat, is not hers here
+        let mut commit = init(10, "alice");
+        commit.seat.game = "other".into();
+        assert_eq!(
+            referee.new_game(GAME, "alice", commit, None, None),
+            Err(BattleshipError::WrongSeat)
+        );
+        assert_eq!(
+            referee.new_game(GAME, "alice", init(10, "bob"), None, None),
+            Err(BattleshipError::WrongSeat)
+        );
+
+        // bob cannot join with alice's receipt
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, None)
+            .unwrap();
+        assert_eq!(
+            referee.join_game(
+                &mut state,
+                GAME,
+                "bob",
+                init(10, "alice"),
+                Position::new(1, 1)
+            ),
+            Err(BattleshipError::WrongSeat)
+        );
+        assert_eq!(state.next_turn, 0);
+
+        // nor answer a shot with an answer proven for alice
+        let mut state = started();
+        let before = state.clone();
+        let commit = round("bob", 10, 11, 1, 1, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "alice", commit, Position::new(0, 0)),
+            Err(BattleshipError::WrongSeat)
+        );
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn rejected_turns() {
+        let referee = Referee::new(0);
+        let mut state = started();
+        let before = state.clone();
+
+        let commit = round("bob", 10, 11, 1, 1, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "bob", commit, Position::new(0, 0)),
+            Err(BattleshipError::NotYourTurn)
+        );
+        let commit = round("alice", 99, 11, 1, 1, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "alice", commit, Position::new(0, 0)),
+            Err(BattleshipError::StateMismatch)
+        );
+        let commit = round("alice", 10, 11, 5, 5, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "alice", commit, Position::new(0, 0)),
+            Err(BattleshipError::ShotMismatch)
+        );
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn shots() {
+        let referee = Referee::new(0);
+        let mut state = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, None)
+            .unwrap();
+        assert_eq!(
+            referee.join_game(
+                &mut state,
+                GAME,
+                "bob",
+                init(20, "bob"),
+                Position::new(10, 0)
+            ),
+            Err(BattleshipError::ShotOutOfBounds)
+        );
+        assert_eq!(state.next_turn, 0);
+
+        let mut state = started();
+        assert_eq!(state.p2.shots, vec![11]);
+        let before = state.clone();
+        let commit = round("alice", 10, 11, 1, 1, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "alice", commit, Position::new(0, 50)),
+            Err(BattleshipError::ShotOutOfBounds)
+        );
+        assert_eq!(state, before);
+
+        let commit = round("alice", 10, 11, 1, 1, HitType::Miss);
+        referee
+            .turn(&mut state, GAME, "alice", commit, Position::new(2, 2))
+            .unwrap();
+        let commit = round("bob", 20, 21, 2, 2, HitType::Miss);
+        assert_eq!(
+            referee.turn(&mut state, GAME, "bob", commit, Position::new(1, 1)),
+            Err(BattleshipError::DuplicateShot)
+        );
+        let commit = round("bob", 20, 21, 2, 2, HitType::Miss);
+        referee
+            .turn(&mut state, GAME, "bob", commit, Position::new(2, 2))
+            .unwrap();
+        assert_eq!(state.p1.shots, vec![22]);
+        assert_eq!(state.p2.shots, vec![11, 22]);
+    }
+
+    #[test]
+    fn forfeit() {
+        let mut state = started();
+        let timeout = state.turn_timeout_ms;
+
+        let referee = Referee::new(timeout);
+        assert_eq!(
+            referee.claim_forfeit(&mut state, "bob"),
+            Err(BattleshipError::DeadlineNotPassed)
+        );
+
+        let referee = Referee::new(timeout + 1);
+        assert_eq!(
+            referee.claim_forfeit(&mut state, "alice"),
+            Err(BattleshipError::NotAllowed)
+        );
+        assert!(!referee.may_delete(&state, "carol"));
+        referee.claim_forfeit(&mut state, "bob").unwrap();
+        assert_eq!(state.winner.as_deref(), Some("bob"));
+        assert!(referee.may_delete(&state, "carol"));
+
+        // A deadline past the end of time never passes, rather than wrapping
+        let mut state = started();
+        state.last_move_ms = u64::MAX - 1;
+        assert!(!state.deadline_passed(u64::MAX));
+    }
+
+    #[test]
+    fn error_codes() {
+        for err in BattleshipError::ALL {
+            let msg = format!("Smart contract panicked: {}", err.code());
+            assert_eq!(BattleshipError::from_message(&msg), Some(err));
+        }
+        assert_eq!(BattleshipError::from_message("out of gas"), None);
+    }
+
+    #[test]
+    fn delete() {
+        let referee = Referee::new(0);
+        let open = referee
+            .new_game(GAME, "alice", init(10, "alice"), None, None)
+            .unwrap();
+        assert!(referee.may_delete(&open, "alice"));
+        assert!(!referee.may_delete(&open, "carol"));
+
+        // An open game nobody joined may be cleaned up once its deadline passes
+        let late = Referee::new(open.turn_timeout_ms + 1);
+        assert!(late.may_delete(&open, "carol"));
+
+        // Once joined, not even a participant may delete a game in play
+        let mut state = started();
+        assert!(!referee.may_delete(&state, "alice"));
+        assert!(!referee.may_delete(&state, "bob"));
+        assert!(!late.may_delete(&state, "alice"));
+        assert!(!late.may_delete(&state, "carol"));
+
+        state.next_turn = GAME_OVER;
+        assert!(referee.may_delete(&state, "alice"));
+        assert!(referee.may_delete(&state, "carol"));
+    }
+
+    #[test]
+    fn reveal() {
+        let referee = Referee::new(0);
+        let mut state = started();
+        let commit = RevealCommit {
+            state: digest(20),
+            ships: vec![Ship::with_hit_mask(0, 0, ShipDirection::Vertical, 0x03)],
+        };
+        assert_eq!(
+            referee.reveal(&mut state, commit),
+            Err(BattleshipError::WrongTurn)
+        );
+        assert!(!referee.may_delete(&state, "carol"));
+
+        state.next_turn = GAME_OVER;
+        let commit = RevealCommit {
+            state: digest(30),
+            ships: vec![],
+        };
+        assert_eq!(
+            referee.reveal(&mut state, commit),
+            Err(BattleshipError::StateMismatch)
+        );
+        let commit = RevealCommit {
+            state: digest(20),
+            ships: vec![Ship::with_hit_mask(0, 0, ShipDirection::Vertical, 0x03)],
+        };
+        referee.reveal(&mut state, commit).unwrap();
+        let revealed = state.p2.revealed.unwrap();
+        assert!(revealed[0].vertical);
+        assert_eq!(revealed[0].hit_mask, 0x03);
+        assert!(state.p1.revealed.is_none());
+    }
+}
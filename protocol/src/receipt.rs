@@ -0,0 +1,236 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+#[cfg(feature = "verify")]
+use risc0_zkvm_verify::zkvm::{MethodID, Receipt};
+#[cfg(feature = "verify")]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::BattleshipError;
+
+/// Bumped whenever the envelope or its payload changes shape.
+pub const RECEIPT_VERSION: u32 = 1;
+
+/// A zkVM receipt as provers send it, wrapped in a [ReceiptEnvelope]. It
+/// deserializes as the verifier's own receipt type.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WireReceipt {
+    pub journal: Vec<u8>,
+    pub seal: Vec<u32>,
+}
+
+impl WireReceipt {
+    /// Encodes this receipt in an envelope claiming it proves `method_id`.
+    pub fn encode(&self, method_id: &[u8]) -> String {
+        ReceiptEnvelope::new(method_id, bincode::serialize(self).unwrap()).encode()
+    }
+}
+
+/// How receipts travel between provers, players and hosts: base64 over
+/// bincode, tagged with a format version and the method they claim to prove.
+/// The payload is the bincode-encoded `{ journal, seal }` receipt itself.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReceiptEnvelope {
+    pub version: u32,
+    pub method_id: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReceiptError {
+    // Not base64, or not an envelope at all.
+    Malformed,
+    // An envelope in a format this build does not understand.
+    UnsupportedVersion(u32),
+    // An envelope for a different guest method than the one expected.
+    WrongMethod,
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::Malformed => write!(f, "malformed receipt"),
+            ReceiptError::UnsupportedVersion(version) => write!(
+                f,
+                "receipt format version {} is not supported, expected {}",
+                version, RECEIPT_VERSION
+            ),
+            ReceiptError::WrongMethod => write!(f, "receipt is for a different method"),
+        }
+    }
+}
+
+impl std::error::Error for ReceiptError {}
+
+impl From<ReceiptError> for BattleshipError {
+    fn from(err: ReceiptError) -> Self {
+        match err {
+            ReceiptError::Malformed => BattleshipError::BadReceipt,
+            ReceiptError::UnsupportedVersion(_) => BattleshipError::UnsupportedReceipt,
+            ReceiptError::WrongMethod => BattleshipError::WrongMethod,
+        }
+    }
+}
+
+impl ReceiptEnvelope {
+    pub fn new(method_id: &[u8], payload: Vec<u8>) -> Self {
+        ReceiptEnvelope {
+            version: RECEIPT_VERSION,
+            method_id: method_id.to_vec(),
+            payload,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        base64::encode(bincode::serialize(self).unwrap())
+    }
+
+    pub fn decode(receipt: &str) -> Result<Self, ReceiptError> {
+        let bytes = base64::decode(receipt.trim()).map_err(|_| ReceiptError::Malformed)?;
+        // The version leads every format, so check it before trusting the rest
+        let version: u32 = bincode::deserialize(&bytes).map_err(|_| ReceiptError::Malformed)?;
+        if version != RECEIPT_VERSION {
+            return Err(ReceiptError::UnsupportedVersion(version));
+        }
+        bincode::deserialize(&bytes).map_err(|_| ReceiptError::Malformed)
+    }
+
+    /// Decodes an envelope and returns its payload, provided it claims to
+    /// prove `method_id`. The payload still has to be verified.
+    pub fn open(receipt: &str, method_id: &[u8]) -> Result<Vec<u8>, ReceiptError> {
+        let envelope = Self::decode(receipt)?;
+        if envelope.method_id != method_id {
+            return Err(ReceiptError::WrongMethod);
+        }
+        Ok(envelope.payload)
+    }
+}
+
+#[cfg(feature = "verify")]
+#[derive(Debug)]
+pub enum VerifyError {
+    // The envelope is malformed, from another format version or for
+    // another method.
+    Envelope(ReceiptError),
+    // The receipt inside the envelope is not valid bincode.
+    Malformed,
+    // The seal does not verify against the expected method.
+    Invalid,
+    // The journal does not hold the expected commitment.
+    Journal,
+}
+
+#[cfg(feature = "verify")]
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Envelope(err) => write!(f, "{}", err),
+            VerifyError::Malformed => write!(f, "malformed receipt"),
+            VerifyError::Invalid => write!(f, "receipt does not verify"),
+            VerifyError::Journal => write!(f, "unexpected journal contents"),
+        }
+    }
+}
+
+#[cfg(feature = "verify")]
+impl std::error::Error for VerifyError {}
+
+#[cfg(feature = "verify")]
+impl From<VerifyError> for BattleshipError {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::Envelope(err) => err.into(),
+            VerifyError::Malformed | VerifyError::Invalid | VerifyError::Journal => {
+                BattleshipError::BadReceipt
+            }
+        }
+    }
+}
+
+/// Checks a receipt in the format produced by the prover against `method_id`
+/// and decodes its journal.
+#[cfg(feature = "verify")]
+pub fn verify_receipt<T: DeserializeOwned>(
+    receipt: &str,
+    method_id: &[u8],
+) -> Result<T, VerifyError> {
+    decode_receipt(receipt, method_id, true)
+}
+
+/// Decodes the journal of a receipt made for `method_id`, verifying its seal
+/// only when asked to. Dev-mode receipts have no seal to verify.
+#[cfg(feature = "verify")]
+pub fn decode_receipt<T: DeserializeOwned>(
+    receipt: &str,
+    method_id: &[u8],
+    check_seal: bool,
+) -> Result<T, VerifyError> {
+    let payload = ReceiptEnvelope::open(receipt, method_id).map_err(VerifyError::Envelope)?;
+    let receipt = bincode::deserialize::<Receipt>(&payload).map_err(|_| VerifyError::Malformed)?;
+    if check_seal {
+        let method_id = MethodID::try_from(method_id).map_err(|_| VerifyError::Invalid)?;
+        receipt
+            .verify(&method_id)
+            .map_err(|_| VerifyError::Invalid)?;
+    }
+    let journal = receipt.get_journal_u32();
+    risc0_zkvm_serde::from_slice::<T>(&journal).map_err(|_| VerifyError::Journal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope() {
+        let receipt = ReceiptEnvelope::new(&[1, 2, 3], vec![4, 5, 6]).encode();
+        assert_eq!(
+            ReceiptEnvelope::open(&receipt, &[1, 2, 3]),
+            Ok(vec![4, 5, 6])
+        );
+        assert_eq!(
+            ReceiptEnvelope::open(&receipt, &[3, 2, 1]),
+            Err(ReceiptError::WrongMethod)
+        );
+
+        let mut envelope = ReceiptEnvelope::new(&[1, 2, 3], vec![]);
+        envelope.version = RECEIPT_VERSION + 1;
+        assert_eq!(
+            ReceiptEnvelope::decode(&envelope.encode()),
+            Err(ReceiptError::UnsupportedVersion(RECEIPT_VERSION + 1))
+        );
+
+        let receipt = WireReceipt {
+            journal: vec![7, 8],
+            seal: vec![9],
+        };
+        let payload = ReceiptEnvelope::open(&receipt.encode(&[1, 2, 3]), &[1, 2, 3]).unwrap();
+        assert_eq!(
+            bincode::deserialize::<WireReceipt>(&payload).unwrap(),
+            receipt
+        );
+
+        assert_eq!(
+            ReceiptEnvelope::decode("not a receipt"),
+            Err(ReceiptError::Malformed)
+        );
+        assert_eq!(
+            ReceiptEnvelope::decode(&base64::encode([1, 0, 0, 0, 9])),
+            Err(ReceiptError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, panic, path::Path};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use battleship_core::{
+    GameState, InitCommit, InitParams, RevealCommit, RoundCommit, RoundParams, RoundResult,
+};
+use battleship_methods::{INIT_ID, INIT_PATH, REVEAL_ID, REVEAL_PATH, TURN_ID, TURN_PATH};
+use battleship_protocol::WireReceipt;
+use risc0_zkvm_core::Digest;
+use risc0_zkvm_host::{Exception, Prover};
+
+#[derive(Deserialize, Serialize)]
+pub struct TurnResult {
+    pub state: RoundResult,
+    pub receipt: String,
+}
+
+#[derive(Debug)]
+pub enum ProverError {
+    // The zkVM failed to load or run a guest.
+    Zkvm(Exception),
+    // The guest logic rejected its input.
+    Rejected(String),
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProverError::Zkvm(err) => write!(f, "zkvm: {:?}", err),
+            ProverError::Rejected(msg) => write!(f, "rejected: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<Exception> for ProverError {
+    fn from(err: Exception) -> Self {
+        ProverError::Zkvm(err)
+    }
+}
+
+/// Produces the enveloped receipts consumed by the contract for each guest.
+pub trait ProverBackend: Send + Sync {
+    fn prove_init(&self, params: &InitParams) -> Result<String, ProverError>;
+    fn prove_turn(&self, params: &RoundParams) -> Result<TurnResult, ProverError>;
+    fn prove_reveal(&self, state: &GameState) -> Result<String, ProverError>;
+}
+
+/// Proves every request with the zkVM, using guest ELFs loaded once at startup.
+pub struct LocalProver {
+    init_elf: Vec<u8>,
+    turn_elf: Vec<u8>,
+    reveal_elf: Vec<u8>,
+}
+
+impl LocalProver {
+    pub fn new(init: &Path, turn: &Path, reveal: &Path) -> std::io::Result<Self> {
+        Ok(LocalProver {
+            init_elf: std::fs::read(init)?,
+            turn_elf: std::fs::read(turn)?,
+            reveal_elf: std::fs::read(reveal)?,
+        })
+    }
+
+    /// Loads the guests from where `battleship-methods` built them.
+    pub fn load() -> std::io::Result<Self> {
+        Self::new(
+            Path::new(INIT_PATH),
+            Path::new(TURN_PATH),
+            Path::new(REVEAL_PATH),
+        )
+    }
+
+    fn run(
+        &self,
+        elf: &[u8],
+        id: &[u8],
+        input: &[u32],
+    ) -> Result<(WireReceipt, Prover), Exception> {
+        let mut prover = Prover::new(elf, id)?;
+        prover.add_input(input)?;
+        let receipt = prover.run()?;
+        let receipt = WireReceipt {
+            journal: receipt.get_journal().unwrap().to_vec(),
+            seal: receipt.get_seal().unwrap().to_vec(),
+        };
+        Ok((receipt, prover))
+    }
+}
+
+impl ProverBackend for LocalProver {
+    fn prove_init(&self, params: &InitParams) -> Result<String, ProverError> {
+        let input = risc0_zkvm_serde::to_vec(params).unwrap();
+        let (receipt, _) = self.run(&self.init_elf, INIT_ID, &input)?;
+        Ok(receipt.encode(INIT_ID))
+    }
+
+    fn prove_turn(&self, params: &RoundParams) -> Result<TurnResult, ProverError> {
+        let input = risc0_zkvm_serde::to_vec(params).unwrap();
+        let (receipt, prover) = self.run(&self.turn_elf, TURN_ID, &input)?;
+        let vec = prover.get_output_vec()?;
+        let result = risc0_zkvm_serde::from_slice::<RoundResult>(vec.as_slice()).unwrap();
+        Ok(TurnResult {
+            state: result,
+            receipt: receipt.encode(TURN_ID),
+        })
+    }
+
+    fn prove_reveal(&self, state: &GameState) -> Result<String, ProverError> {
+        let input = risc0_zkvm_serde::to_vec(state).unwrap();
+        let (receipt, _) = self.run(&self.reveal_elf, REVEAL_ID, &input)?;
+        Ok(receipt.encode(REVEAL_ID))
+    }
+}
+
+/// Runs the guest logic natively and returns receipts with the expected
+/// journal but an empty seal. These receipts will not verify against the
+/// real method IDs, and their digests are not interchangeable with those
+/// produced by the zkVM; they are only meant for UI work and end-to-end
+/// tests where full proving is too slow.
+pub struct DevProver;
+
+impl DevProver {
+    fn digest<T: Serialize>(value: &T) -> Digest {
+        let mut hasher = Sha256::new();
+        for word in risc0_zkvm_serde::to_vec(value).unwrap() {
+            hasher.update(word.to_le_bytes());
+        }
+        let hash = hasher.finalize();
+        let mut words = [0u32; 8];
+        for (word, chunk) in words.iter_mut().zip(hash.chunks(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Digest::new(words)
+    }
+
+    fn fake_receipt<T: Serialize>(method_id: &[u8], commit: &T) -> String {
+        let journal = risc0_zkvm_serde::to_vec(commit)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        WireReceipt {
+            journal,
+            seal: Vec::new(),
+        }
+        .encode(method_id)
+    }
+}
+
+impl ProverBackend for DevProver {
+    fn prove_init(&self, params: &InitParams) -> Result<String, ProverError> {
+        if !params.state.check() {
+            return Err(ProverError::Rejected("Invalid GameState".into()));
+        }
+        Ok(Self::fake_receipt(
+            INIT_ID,
+            &InitCommit {
+                state: Self::digest(&params.state),
+                rules: params.state.rules.clone(),
+                seat: params.seat.clone(),
+            },
+        ))
+    }
+
+    fn prove_turn(&self, params: &RoundParams) -> Result<TurnResult, ProverError> {
+        // The guest rejects a malformed fleet by panicking.
+        let result = panic::catch_unwind(|| params.process())
+            .map_err(|_| ProverError::Rejected("Invalid RoundParams".into()))?;
+        let receipt = Self::fake_receipt(
+            TURN_ID,
+            &RoundCommit {
+                old_state: Self::digest(&params.state),
+                new_state: Self::digest(&result.state),
+                shot: params.shot.clone(),
+                hit: result.hit.clone(),
+                fleet_destroyed: result.fleet_destroyed,
+                seat: params.seat.clone(),
+                shots_taken: params.state.shots.len(),
+            },
+        );
+        Ok(TurnResult {
+            state: result,
+            receipt,
+        })
+    }
+
+    fn prove_reveal(&self, state: &GameState) -> Result<String, ProverError> {
+        Ok(Self::fake_receipt(
+            REVEAL_ID,
+            &RevealCommit {
+                state: Self::digest(state),
+                ships: state.ships.clone(),
+            },
+        ))
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use clap::Args;
+
+use battleship_methods::{INIT_PATH, REVEAL_PATH, TURN_PATH};
+
+/// Options shared by every service that proves moves.
+#[derive(Args)]
+pub struct ProverConfig {
+    /// Guest ELF used for init proofs.
+    #[clap(long, env = "BATTLESHIP_INIT_ELF", default_value = INIT_PATH)]
+    pub init_elf: PathBuf,
+
+    /// Guest ELF used for turn proofs.
+    #[clap(long, env = "BATTLESHIP_TURN_ELF", default_value = TURN_PATH)]
+    pub turn_elf: PathBuf,
+
+    /// Guest ELF used for reveal proofs.
+    #[clap(long, env = "BATTLESHIP_REVEAL_ELF", default_value = REVEAL_PATH)]
+    pub reveal_elf: PathBuf,
+
+    /// Number of proofs to run at once; defaults to one per core.
+    #[clap(long, env = "BATTLESHIP_PROVER_WORKERS")]
+    pub workers: Option<usize>,
+
+    /// Skip proving and return unverifiable receipts.
+    #[clap(long, env = "BATTLESHIP_DEV_MODE")]
+    pub dev_mode: bool,
+}
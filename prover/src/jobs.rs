@@ -0,0 +1,153 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::ProverError;
+
+// Random, so one client cannot guess another's job and read its output.
+pub type JobId = String;
+
+// How long a finished job waits to be fetched before it is dropped.
+const JOB_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done { output: String },
+    Failed { error: String },
+}
+
+impl JobStatus {
+    fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done { .. } | JobStatus::Failed { .. })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JobTicket {
+    pub id: JobId,
+}
+
+struct Job {
+    status: JobStatus,
+    updated: Instant,
+}
+
+/// Runs proving jobs on the blocking thread pool, at most `workers` at a time,
+/// and keeps their status around until a finished job is fetched or expires.
+pub struct JobQueue {
+    jobs: Mutex<HashMap<JobId, Job>>,
+    workers: Arc<Semaphore>,
+}
+
+impl JobQueue {
+    pub fn new(workers: usize) -> Self {
+        JobQueue {
+            jobs: Mutex::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(workers)),
+        }
+    }
+
+    pub fn submit<F>(self: &Arc<Self>, job: F) -> JobId
+    where
+        F: FnOnce() -> Result<String, ProverError> + Send + 'static,
+    {
+        self.expire();
+        let id = format!("{:032x}", rand::random::<u128>());
+        self.set(&id, JobStatus::Queued);
+        let queue = self.clone();
+        let job_id = id.clone();
+        tokio::spawn(async move {
+            let id = job_id;
+            let _permit = queue.workers.clone().acquire_owned().await.unwrap();
+            queue.set(&id, JobStatus::Running);
+            let status = match tokio::task::spawn_blocking(job).await {
+                Ok(Ok(output)) => JobStatus::Done { output },
+                Ok(Err(err)) => {
+                    tracing::error!("job {} failed: {}", id, err);
+                    JobStatus::Failed {
+                        error: err.to_string(),
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("job {} panicked: {}", id, err);
+                    JobStatus::Failed {
+                        error: String::from("prover panicked"),
+                    }
+                }
+            };
+            queue.set(&id, status);
+        });
+        id
+    }
+
+    /// Returns the job's status. A finished job is handed out only once.
+    pub fn status(&self, id: &str) -> Option<JobStatus> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.get(id)?.status.is_finished() {
+            jobs.remove(id).map(|job| job.status)
+        } else {
+            jobs.get(id).map(|job| job.status.clone())
+        }
+    }
+
+    fn set(&self, id: &str, status: JobStatus) {
+        let job = Job {
+            status,
+            updated: Instant::now(),
+        };
+        self.jobs.lock().unwrap().insert(id.to_string(), job);
+    }
+
+    // Drops finished jobs nobody came back for.
+    fn expire(&self) {
+        self.jobs
+            .lock()
+            .unwrap()
+            .retain(|_, job| !job.status.is_finished() || job.updated.elapsed() < JOB_TTL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fetched_once() {
+        let queue = Arc::new(JobQueue::new(1));
+        let id = queue.submit(|| Ok(String::from("receipt")));
+        assert_eq!(id.len(), 32);
+        assert_ne!(id, queue.submit(|| Ok(String::new())));
+
+        let output = loop {
+            match queue.status(&id) {
+                Some(JobStatus::Done { output }) => break output,
+                Some(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                None => panic!("job went missing"),
+            }
+        };
+        assert_eq!(output, "receipt");
+        assert!(queue.status(&id).is_none());
+    }
+}
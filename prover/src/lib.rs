@@ -0,0 +1,154 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The proving service behind the web client: `/prove/*` endpoints that
+//! queue a proof and `/jobs/:id` to poll for the receipt. Job output is only
+//! ever a receipt, never the caller's secret state. The web server
+//! mounts these routes, and the `battleship-prover` binary serves them on
+//! the player's own machine so their fleet never leaves it.
+
+mod backend;
+mod config;
+mod jobs;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+
+use battleship_core::{GameError, GameState, InitParams, RoundParams};
+
+pub use backend::{DevProver, LocalProver, ProverBackend, ProverError, TurnResult};
+pub use config::ProverConfig;
+pub use jobs::{JobId, JobQueue, JobStatus, JobTicket};
+
+type Backend = Arc<dyn ProverBackend>;
+type Jobs = Arc<JobQueue>;
+
+#[derive(Serialize)]
+struct InvalidInput {
+    error: String,
+    reason: GameError,
+}
+
+type Rejection = (StatusCode, Json<InvalidInput>);
+
+// Input the guest would reject is the caller's fault, not a prover failure.
+fn invalid_input(reason: GameError) -> Rejection {
+    tracing::warn!("invalid input: {}", reason);
+    let body = InvalidInput {
+        error: reason.to_string(),
+        reason,
+    };
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(body))
+}
+
+/// Builds the proving routes, loading the guest ELFs named by `config`.
+pub fn routes(config: &ProverConfig) -> Router {
+    let backend: Backend = if config.dev_mode {
+        tracing::warn!("dev mode: receipts are not proven and will not verify");
+        Arc::new(DevProver)
+    } else {
+        let prover = LocalProver::new(&config.init_elf, &config.turn_elf, &config.reveal_elf)
+            .expect("failed to load guest ELFs");
+        Arc::new(prover)
+    };
+
+    // Proofs are CPU bound, so by default run one per core.
+    let workers = config
+        .workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    tracing::info!("proving with {} workers", workers);
+    let jobs: Jobs = Arc::new(JobQueue::new(workers));
+
+    Router::new()
+        .route("/prove/init", post(prove_init))
+        .route("/prove/turn", post(prove_turn))
+        .route("/prove/reveal", post(prove_reveal))
+        .route("/jobs/:id", get(job_status))
+        .layer(Extension(backend))
+        .layer(Extension(jobs))
+}
+
+// Resolves on Ctrl-C, or on SIGTERM where available, so in-flight requests
+// can finish before the server exits.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.unwrap();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .unwrap()
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("shutting down");
+}
+
+async fn prove_init(
+    Extension(backend): Extension<Backend>,
+    Extension(jobs): Extension<Jobs>,
+    Json(payload): Json<InitParams>,
+) -> Result<impl IntoResponse, Rejection> {
+    payload.state.validate().map_err(invalid_input)?;
+    let id = jobs.submit(move || backend.prove_init(&payload));
+    Ok((StatusCode::ACCEPTED, Json(JobTicket { id })))
+}
+
+async fn prove_turn(
+    Extension(backend): Extension<Backend>,
+    Extension(jobs): Extension<Jobs>,
+    Json(payload): Json<RoundParams>,
+) -> Result<impl IntoResponse, Rejection> {
+    payload.validate().map_err(invalid_input)?;
+    // The caller runs the same round logic itself to get the new state
+    let id = jobs.submit(move || Ok(backend.prove_turn(&payload)?.receipt));
+    Ok((StatusCode::ACCEPTED, Json(JobTicket { id })))
+}
+
+async fn prove_reveal(
+    Extension(backend): Extension<Backend>,
+    Extension(jobs): Extension<Jobs>,
+    Json(payload): Json<GameState>,
+) -> impl IntoResponse {
+    let id = jobs.submit(move || backend.prove_reveal(&payload));
+    (StatusCode::ACCEPTED, Json(JobTicket { id }))
+}
+
+async fn job_status(Extension(jobs): Extension<Jobs>, Path(id): Path<JobId>) -> Response {
+    match jobs.status(&id) {
+        Some(status @ JobStatus::Failed { .. }) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(status)).into_response()
+        }
+        Some(status) => Json(status).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("no such job: {}", id)).into_response(),
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    http::{header, HeaderValue, Method},
+    routing::get,
+    Json, Router,
+};
+use clap::Parser;
+use serde_json::{json, Value};
+use tower_http::{
+    cors::{CorsLayer, Origin},
+    trace::TraceLayer,
+};
+use tracing_subscriber::prelude::*;
+
+use battleship_prover::{routes, shutdown_signal, ProverConfig};
+
+/// Proves moves for the battleship web client on this machine, so that the
+/// secret fleet is never sent to anyone else.
+#[derive(Parser)]
+#[clap(version)]
+struct Config {
+    /// Address to listen on. Anything but loopback exposes the fleet again.
+    #[clap(long, env = "BATTLESHIP_PROVER_ADDR", default_value = "127.0.0.1")]
+    addr: IpAddr,
+
+    /// Port to listen on; the web client looks for the prover here.
+    #[clap(long, env = "BATTLESHIP_PROVER_PORT", default_value_t = 3001)]
+    port: u16,
+
+    /// Origins the web client is served from.
+    #[clap(
+        long,
+        env = "BATTLESHIP_ALLOW_ORIGIN",
+        use_value_delimiter = true,
+        default_value = "http://localhost:8080,http://127.0.0.1:8080"
+    )]
+    allow_origin: Vec<HeaderValue>,
+
+    /// Tracing filter directives.
+    #[clap(long, env = "RUST_LOG", default_value = "info,battleship_prover=debug")]
+    log: String,
+
+    #[clap(flatten)]
+    prover: ProverConfig,
+}
+
+// Lets the web client discover the sidecar before sending it a fleet.
+async fn health() -> Json<Value> {
+    Json(json!({
+        "service": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+#[tokio::main]
+async fn main() {
+    let config = Config::parse();
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&config.log))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_level(true)
+                .compact(),
+        )
+        .try_init()
+        .unwrap();
+
+    if !config.addr.is_loopback() {
+        tracing::warn!(
+            "listening on {}, other machines can see fleets",
+            config.addr
+        );
+    }
+
+    // The web client is served from another origin than the sidecar
+    let cors = CorsLayer::new()
+        .allow_origin(Origin::list(config.allow_origin.clone()))
+        .allow_methods(vec![Method::GET, Method::POST])
+        .allow_headers(vec![header::CONTENT_TYPE]);
+
+    let app = Router::new()
+        .route("/health", get(health))
+        .merge(routes(&config.prover))
+        .layer(cors)
+        .layer(TraceLayer::new_for_http());
+
+    let addr = SocketAddr::from((config.addr, config.port));
+    tracing::info!("listening on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+}
@@ -13,15 +13,20 @@
 // limitations under the License.
 
 const GAS = "300000000000000";
-const CONTRACT_ID = "dev-1654589053728-38255945982145";
+// Set per deployment through the battleship-contract meta tag in index.html.
+const CONTRACT_ID = document
+  .querySelector('meta[name="battleship-contract"]')
+  ?.getAttribute("content");
 const NETWORK_ID = "testnet";
 
-const VIEW_METHODS = ["game_state", "list_games"];
+const VIEW_METHODS = ["game_state", "list_games", "owner"];
 
 const CHANGE_METHODS = [
   "new_game",
   "join_game",
   "turn",
+  "reveal",
+  "claim_forfeit",
   "clear_games",
   "delete_game",
 ];
@@ -32,6 +37,12 @@ export class NearWallet {
   wallet;
 
   constructor() {
+    if (!CONTRACT_ID) {
+      throw new Error(
+        "No contract configured: set the battleship-contract meta tag in index.html"
+      );
+    }
+
     // connect to NEAR
     const near = new nearApi.Near({
       NETWORK_ID,
@@ -94,6 +105,10 @@ export class NearContract {
     return this.contract.list_games();
   }
 
+  owner() {
+    return this.contract.owner();
+  }
+
   clear_games() {
     return this.contract.clear_games({}, GAS);
   }
@@ -102,8 +117,16 @@ export class NearContract {
     return this.contract.game_state({ name });
   }
 
-  new_game(name, receipt_str) {
-    return this.contract.new_game({ name, receipt_str }, GAS);
+  new_game(name, receipt_str, opponent, turn_timeout_secs) {
+    return this.contract.new_game(
+      {
+        name,
+        receipt_str,
+        opponent: opponent ?? null,
+        turn_timeout_secs: turn_timeout_secs ?? null,
+      },
+      GAS
+    );
   }
 
   join_game(name, receipt_str, shot_x, shot_y) {
@@ -113,4 +136,12 @@ export class NearContract {
   turn(name, receipt_str, shot_x, shot_y) {
     return this.contract.turn({ name, receipt_str, shot_x, shot_y }, GAS);
   }
+
+  reveal(name, receipt_str) {
+    return this.contract.reveal({ name, receipt_str }, GAS);
+  }
+
+  claim_forfeit(name) {
+    return this.contract.claim_forfeit({ name }, GAS);
+  }
 }
@@ -14,7 +14,7 @@
 
 use std::collections::HashMap;
 
-use battleship_core::{GameState, Position, ShipDirection, NUM_SHIPS, SHIP_SPANS};
+use battleship_core::{GameRules, GameState, Position, ShipDirection, NUM_SHIPS, SHIP_SPANS};
 use yew::{context::ContextHandle, prelude::*};
 use yew_agent::{Dispatched, Dispatcher};
 
@@ -23,11 +23,21 @@ use crate::{
     game::{CoreHitType, GameMsg, GameSession, HitType, Side},
 };
 
-const BOARD_SIZE: usize = 10;
-
 const SHIP_NAMES: [&str; NUM_SHIPS] =
     ["carrier", "battleship", "cruiser", "submarine", "destroyer"];
 
+// Picks artwork for a ship: its usual slot in the standard fleet if the span
+// matches, otherwise the first standard ship of the same span.
+fn ship_name(idx: usize, span: usize) -> Option<&'static str> {
+    if SHIP_SPANS.get(idx) == Some(&span) {
+        return Some(SHIP_NAMES[idx]);
+    }
+    SHIP_SPANS
+        .iter()
+        .position(|s| *s == span)
+        .map(|idx| SHIP_NAMES[idx])
+}
+
 pub enum Msg {
     GameUpdate(GameSession),
     Shot(Position),
@@ -44,6 +54,7 @@ enum Foreground {
 #[derive(Copy, Clone)]
 enum Background {
     Vacant,
+    Unnamed,
     Occupied {
         ship: &'static str,
         dir: &'static str,
@@ -75,6 +86,9 @@ impl Into<Classes> for &Cell {
             Foreground::Miss => ret.push("miss"),
             Foreground::Pending => ret.push("pending"),
         };
+        if let Background::Unnamed = self.bg {
+            ret.push("ship");
+        }
         ret
     }
 }
@@ -82,7 +96,7 @@ impl Into<Classes> for &Cell {
 impl Cell {
     fn img(&self) -> Option<String> {
         match &self.bg {
-            Background::Vacant => None,
+            Background::Vacant | Background::Unnamed => None,
             Background::Occupied { ship, dir, idx } => {
                 Some(format!("/assets/ships/{}/{}/{}.png", ship, dir, idx).into())
             }
@@ -90,7 +104,11 @@ impl Cell {
     }
 }
 
-type Cells = [[Cell; BOARD_SIZE]; BOARD_SIZE];
+type Cells = Vec<Vec<Cell>>;
+
+fn empty_cells(rules: &GameRules) -> Cells {
+    vec![vec![Cell::default(); rules.width as usize]; rules.height as usize]
+}
 
 struct Grid {
     pub cells: Cells,
@@ -100,57 +118,53 @@ impl Grid {
     pub fn render(game: &GameSession, side: Side) -> Self {
         match side {
             Side::Local => Grid::render_local(&game.local_shots, &game.state),
-            Side::Remote => Grid::render_remote(&game.remote_shots),
+            Side::Remote => Grid::render_remote(&game.remote_shots, &game.state.rules),
         }
     }
 
     pub fn render_local(shots: &HashMap<Position, HitType>, state: &GameState) -> Self {
-        let mut cells = [[Cell::default(); BOARD_SIZE]; BOARD_SIZE];
-        for i in 0..NUM_SHIPS {
-            let ship = &state.ships[i];
-            let span = SHIP_SPANS[i];
+        let mut cells = empty_cells(&state.rules);
+        for (i, ship) in state.ships.iter().enumerate() {
+            let span = state.rules.span(i);
+            let name = ship_name(i, span);
             let x = ship.pos.x as usize;
             let y = ship.pos.y as usize;
+            let background = |dir: &'static str, idx: usize| match name {
+                Some(ship) => Background::Occupied { ship, dir, idx },
+                None => Background::Unnamed,
+            };
             match ship.dir {
                 ShipDirection::Horizontal => {
                     for dx in 0..span {
-                        cells[y][x + dx].bg = Background::Occupied {
-                            ship: SHIP_NAMES[i].into(),
-                            dir: "h".into(),
-                            idx: dx,
-                        };
+                        cells[y][x + dx].bg = background("h", dx);
                     }
                 }
                 ShipDirection::Vertical => {
                     for dy in 0..span {
-                        cells[y + dy][x].bg = Background::Occupied {
-                            ship: SHIP_NAMES[i].into(),
-                            dir: "v".into(),
-                            idx: dy,
-                        }
+                        cells[y + dy][x].bg = background("v", dy);
                     }
                 }
             }
         }
-        Grid::render_shots(&mut cells, shots)
+        Grid::render_shots(cells, shots)
     }
 
-    pub fn render_remote(shots: &HashMap<Position, HitType>) -> Self {
-        let mut cells = [[Cell::default(); BOARD_SIZE]; BOARD_SIZE];
-        Grid::render_shots(&mut cells, shots)
+    pub fn render_remote(shots: &HashMap<Position, HitType>, rules: &GameRules) -> Self {
+        Grid::render_shots(empty_cells(rules), shots)
     }
 
-    fn render_shots(cells: &mut Cells, shots: &HashMap<Position, HitType>) -> Self {
+    fn render_shots(mut cells: Cells, shots: &HashMap<Position, HitType>) -> Self {
         for (pos, hit) in shots {
             let fg = match hit {
                 &HitType::Core(CoreHitType::Hit) => Foreground::Hit,
                 &HitType::Core(CoreHitType::Miss) => Foreground::Miss,
                 &HitType::Core(CoreHitType::Sunk(_)) => Foreground::Hit,
+                &HitType::Core(CoreHitType::InvalidShot) => Foreground::Miss,
                 &HitType::Pending => Foreground::Pending,
             };
             cells[pos.y as usize][pos.x as usize].fg = fg;
         }
-        Grid { cells: *cells }
+        Grid { cells }
     }
 }
 
@@ -12,19 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
 use async_trait::async_trait;
+use battleship_core::GameRules;
+use battleship_protocol::BattleshipError;
 use serde::{Deserialize, Serialize};
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 
 #[async_trait(?Send)]
 pub trait Contract {
-    async fn list_games(&self) -> Result<Vec<String>, JsValue>;
+    async fn list_games(&self) -> Result<Vec<String>, ContractError>;
+
+    async fn owner(&self) -> Result<String, ContractError>;
 
-    async fn clear_games(&self) -> Result<(), JsValue>;
+    async fn clear_games(&self) -> Result<(), ContractError>;
 
-    async fn get_state(&self, name: &str) -> Result<ContractState, JsValue>;
+    async fn get_state(&self, name: &str) -> Result<ContractState, ContractError>;
 
-    async fn new_game(&self, name: &str, receipt: &str) -> Result<(), JsValue>;
+    async fn new_game(
+        &self,
+        name: &str,
+        receipt: &str,
+        opponent: Option<&str>,
+        turn_timeout_secs: Option<u64>,
+    ) -> Result<(), ContractError>;
 
     async fn join_game(
         &self,
@@ -32,7 +44,7 @@ pub trait Contract {
         receipt: &str,
         shot_x: u32,
         shot_y: u32,
-    ) -> Result<(), JsValue>;
+    ) -> Result<(), ContractError>;
 
     async fn turn(
         &self,
@@ -40,7 +52,53 @@ pub trait Contract {
         receipt: &str,
         shot_x: u32,
         shot_y: u32,
-    ) -> Result<(), JsValue>;
+    ) -> Result<(), ContractError>;
+
+    async fn reveal(&self, name: &str, receipt: &str) -> Result<(), ContractError>;
+
+    async fn claim_forfeit(&self, name: &str) -> Result<(), ContractError>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractError {
+    Battleship(BattleshipError),
+    Other(String),
+}
+
+impl ContractError {
+    // Decodes the stable code the contract panics with, keeping anything else
+    // as the raw message.
+    pub fn from_message(msg: String) -> Self {
+        match BattleshipError::from_message(&msg) {
+            Some(err) => ContractError::Battleship(err),
+            None => ContractError::Other(msg),
+        }
+    }
+}
+
+impl From<BattleshipError> for ContractError {
+    fn from(err: BattleshipError) -> Self {
+        ContractError::Battleship(err)
+    }
+}
+
+impl From<JsValue> for ContractError {
+    fn from(value: JsValue) -> Self {
+        let msg = match value.dyn_ref::<js_sys::Error>() {
+            Some(err) => String::from(err.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{:?}", value)),
+        };
+        ContractError::from_message(msg)
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::Battleship(err) => write!(f, "{}", err),
+            ContractError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -56,15 +114,23 @@ pub struct ContractState {
     // 0 means p1 has setup game, and p2 needs to do setup
     // 1 means p1 needs to process p2's shot and make it's own
     // 2 means p2 needs to process p1's shot and make it's own
+    // 3 means the game is over, see `winner`
     pub next_turn: u32,
+    pub rules: GameRules,
     pub p1: PlayerState,
     pub p2: PlayerState,
-    pub last_hit: Option<u8>,  // 0 = miss, 1 = hit, 2 = sunk
+    pub last_hit: Option<u8>, // 0 = miss, 1 = hit, 2 = sunk, 3 = invalid shot
     pub sunk_what: Option<u8>, // Which ship was sunk
+    pub winner: Option<String>,
+    pub last_move_ms: u64,    // Block timestamp of the last move
+    pub turn_timeout_ms: u64, // How long a player may take before forfeiting
+    #[serde(default)]
+    pub opponent: Option<String>, // The only player who may join, if set
 }
 
 impl ContractState {
-    pub fn from_jsvalue(value: JsValue) -> Self {
-        serde_wasm_bindgen::from_value(value).unwrap()
+    pub fn deadline_passed(&self, now_ms: u64) -> bool {
+        (self.next_turn == 1 || self.next_turn == 2)
+            && now_ms > self.last_move_ms.saturating_add(self.turn_timeout_ms)
     }
 }
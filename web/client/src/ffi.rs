@@ -47,6 +47,9 @@ extern "C" {
     #[wasm_bindgen(method, catch)]
     pub async fn list_games(this: &NearContract) -> Result<JsValue, JsValue>;
 
+    #[wasm_bindgen(method, catch)]
+    pub async fn owner(this: &NearContract) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(method, catch)]
     pub async fn clear_games(this: &NearContract) -> Result<(), JsValue>;
 
@@ -54,7 +57,13 @@ extern "C" {
     pub async fn game_state(this: &NearContract, name: &str) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(method, catch)]
-    pub async fn new_game(this: &NearContract, name: &str, receipt: &str) -> Result<(), JsValue>;
+    pub async fn new_game(
+        this: &NearContract,
+        name: &str,
+        receipt: &str,
+        opponent: Option<&str>,
+        turn_timeout_secs: Option<u32>,
+    ) -> Result<(), JsValue>;
 
     #[wasm_bindgen(method, catch)]
     pub async fn join_game(
@@ -73,4 +82,10 @@ extern "C" {
         shot_x: u32,
         shot_y: u32,
     ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub async fn reveal(this: &NearContract, name: &str, receipt: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub async fn claim_forfeit(this: &NearContract, name: &str) -> Result<(), JsValue>;
 }
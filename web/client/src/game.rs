@@ -17,8 +17,7 @@ use std::{collections::HashMap, rc::Rc};
 use gloo::{
     dialogs::alert, storage::LocalStorage, storage::Storage, timers::future::TimeoutFuture,
 };
-use rand::{thread_rng, Rng};
-use reqwasm::http::Request;
+use rand::{rngs::OsRng, thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use yew::prelude::*;
@@ -27,12 +26,12 @@ use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
 use crate::{
     bus::EventBus,
     contract::{Contract, ContractState},
-    near::NearContract,
-    wallet::WalletContext,
+    prover::prove,
+    wallet::{GameContract, WalletContext},
 };
 use battleship_core::{
-    GameCheck, GameState, Position, RoundParams, RoundResult, Ship, ShipDirection, BOARD_SIZE,
-    SHIP_SPANS,
+    GameCheck, GameRules, GameState, InitParams, Position, RoundParams, RoundResult, Salt, Seat,
+    Ship, ShipDirection,
 };
 
 pub type CoreHitType = battleship_core::HitType;
@@ -45,15 +44,11 @@ pub enum Side {
     Remote,
 }
 
-#[derive(Deserialize, Serialize)]
-pub struct TurnResult {
-    state: RoundResult,
-    receipt: String,
-}
-
 #[derive(Clone, PartialEq)]
 pub enum GameMsg {
     Init,
+    LoadRules,
+    SetRules(GameRules),
     Shot(Position),
     WaitTurn,
     CheckTurn,
@@ -61,6 +56,13 @@ pub enum GameMsg {
     ProcessTurn(ContractState),
     UpdateState(String, RoundResult, Position),
     Resume,
+    GameOver(String),
+    Reveal,
+    Revealed,
+    RevealFailed(String),
+    OpponentTimedOut,
+    ClaimForfeit,
+    ForfeitClaimed,
     Error(String),
 }
 
@@ -85,18 +87,32 @@ pub struct GameSession {
     pub status: String,
     pub og_until: usize,
     pub turn_processed: bool,
+    #[serde(default)]
+    pub game_over: bool,
+    #[serde(default)]
+    pub revealed: bool,
+    #[serde(default)]
+    pub forfeit_claimable: bool,
+}
+
+// Board variants offered when creating a game.
+pub fn variant_rules(variant: &str) -> GameRules {
+    match variant {
+        "quick" => GameRules::new(8, 8, vec![4, 3, 3, 2]),
+        _ => GameRules::default(),
+    }
 }
 
-fn create_random_ships() -> [Ship; 5] {
-    // randomly place 5 ships on the board
+fn create_random_ships(rules: &GameRules) -> Vec<Ship> {
+    // randomly place the fleet on the board
     let mut rng = thread_rng();
     let mut game_check = GameCheck::new();
 
-    let ships: [Ship; 5] = array_init::array_init(|i| {
+    let ships = (0..rules.num_ships()).map(|i| {
         loop {
             // pick a random starting point on the board
-            let x = rng.gen_range(0..BOARD_SIZE - 1);
-            let y = rng.gen_range(0..BOARD_SIZE - 1);
+            let x = rng.gen_range(0..rules.width);
+            let y = rng.gen_range(0..rules.height);
 
             // pick between 0 and 1 for randomized ship placement
             let dir = if rng.gen::<bool>() {
@@ -105,11 +121,11 @@ fn create_random_ships() -> [Ship; 5] {
                 ShipDirection::Vertical
             };
 
-            let ship = Ship::new(x as u32, y as u32, dir);
+            let ship = Ship::new(x, y, dir);
 
             // does it fit on the board
-            let span = SHIP_SPANS[i];
-            if !ship.check(span) {
+            let span = rules.span(i);
+            if !ship.check(span, rules) {
                 continue;
             }
 
@@ -124,15 +140,32 @@ fn create_random_ships() -> [Ship; 5] {
             return ship;
         }
     });
-    ships
+    ships.collect()
+}
+
+fn create_salt() -> Salt {
+    // the salt is all that keeps the board digest from being brute-forced,
+    // so it must come from the browser's CSPRNG
+    let mut salt = Salt::default();
+    OsRng.fill(&mut salt);
+    salt
+}
+
+fn new_game_state(rules: GameRules) -> GameState {
+    let ships = create_random_ships(&rules);
+    GameState::new(rules, ships, create_salt())
 }
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub name: String,
     pub until: usize,
-    #[prop_or_else(create_random_ships)]
-    pub ships: [Ship; 5],
+    #[prop_or_default]
+    pub rules: GameRules,
+    #[prop_or_default]
+    pub opponent: Option<String>, // Only this player may join the new game
+    #[prop_or_default]
+    pub turn_timeout_secs: Option<u64>,
     #[prop_or_default]
     pub children: Children,
 }
@@ -141,7 +174,15 @@ pub struct GameProvider {
     _bridge: Box<dyn Bridge<EventBus<GameMsg>>>,
     journal: Dispatcher<EventBus<String>>,
     game: GameSession,
-    contract: Rc<NearContract>,
+    contract: Rc<GameContract>,
+    player: String,
+}
+
+impl GameProvider {
+    // Receipts are only accepted from the player and game they were proven for
+    fn seat(&self) -> Seat {
+        Seat::new(&self.game.name, &self.player)
+    }
 }
 
 impl Component for GameProvider {
@@ -164,10 +205,7 @@ impl Component for GameProvider {
                 false,
                 GameSession {
                     name: ctx.props().name.clone(),
-                    state: GameState {
-                        ships: ctx.props().ships.clone(),
-                        salt: 0xDEADBEEF,
-                    },
+                    state: new_game_state(ctx.props().rules.clone()),
                     local_shots: HashMap::new(),
                     remote_shots: HashMap::new(),
                     last_receipt: String::new(),
@@ -176,6 +214,9 @@ impl Component for GameProvider {
                     status: format!("Ready!"),
                     og_until: ctx.props().until,
                     turn_processed: false,
+                    game_over: false,
+                    revealed: false,
+                    forfeit_claimable: false,
                 },
             ),
         };
@@ -185,10 +226,14 @@ impl Component for GameProvider {
             log::info!("Game session exists, checking who's turn it is");
             ctx.link().send_message(GameMsg::CheckTurn);
         } else {
-            // if the game session does not exist, initialize the game if player1
+            // if the game session does not exist, initialize the game if player1,
+            // otherwise set up our board under the rules player1 picked
             if ctx.props().until == 1 {
                 log::info!("Game session does not exist, initializing game");
                 ctx.link().send_message(GameMsg::Init);
+            } else {
+                log::info!("Game session does not exist, loading rules");
+                ctx.link().send_message(GameMsg::LoadRules);
             }
         }
 
@@ -198,6 +243,7 @@ impl Component for GameProvider {
             journal: EventBus::dispatcher(),
             game,
             contract,
+            player: wallet.wallet.current_user().unwrap(),
         }
     }
 
@@ -206,38 +252,50 @@ impl Component for GameProvider {
             GameMsg::Init => {
                 self.game.status = format!("Init");
                 let game = self.game.clone();
-                let body = serde_json::to_string(&game.state).unwrap();
                 let contract = self.contract.clone();
+                let params = InitParams::new(game.state.clone(), self.seat());
+                let opponent = ctx.props().opponent.clone();
+                let turn_timeout_secs = ctx.props().turn_timeout_secs;
                 ctx.link().send_future(async move {
-                    let response = match Request::post("/prove/init")
-                        .header("Content-Type", "application/json")
-                        .body(body)
-                        .send()
-                        .await
-                    {
-                        Ok(response) => response,
-                        Err(err) => {
-                            return GameMsg::Error(format!("POST /prove/init failed: {}", err));
-                        }
-                    };
-                    let receipt = match response.text().await {
+                    let receipt = match prove("/prove/init", &params).await {
                         Ok(receipt) => receipt,
-                        Err(err) => {
-                            return GameMsg::Error(format!("receipt: {}", err));
-                        }
+                        Err(err) => return GameMsg::Error(err),
                     };
-                    match contract.new_game(&game.name, &receipt).await {
+                    match contract
+                        .new_game(&game.name, &receipt, opponent.as_deref(), turn_timeout_secs)
+                        .await
+                    {
                         Ok(()) => {
                             log::info!("Game created, save and wait turn {}", game.name);
                             GameMsg::SaveAndWait
                         }
-                        Err(err) => GameMsg::Error(format!("new_game: {:?}", err)),
+                        Err(err) => GameMsg::Error(format!("new_game: {}", err)),
                     }
                 });
                 true
             }
+            GameMsg::LoadRules => {
+                self.game.status = format!("Loading rules");
+                let game = self.game.clone();
+                let contract = self.contract.clone();
+                ctx.link().send_future(async move {
+                    match contract.get_state(&game.name).await {
+                        Ok(state) => GameMsg::SetRules(state.rules),
+                        Err(err) => GameMsg::Error(format!("get_state: {}", err)),
+                    }
+                });
+                true
+            }
+            GameMsg::SetRules(rules) => {
+                self.game.state = new_game_state(rules);
+                self.game.status = format!("Ready!");
+                true
+            }
             GameMsg::Shot(pos) => {
-                if self.game.status == "Ready!" {
+                if self.game.remote_shots.contains_key(&pos) {
+                    alert(&format!("Already fired at {}!", pos));
+                    false
+                } else if self.game.status == "Ready!" {
                     self.game.status = format!("Shot: {}", pos);
                     self.journal.send("GameMsg::Shot".into());
                     self.game.last_shot = Some(pos.clone());
@@ -246,25 +304,12 @@ impl Component for GameProvider {
                     let contract = self.contract.clone();
                     let is_first = self.game.is_first;
                     self.game.is_first = false;
+                    let params = InitParams::new(game.state.clone(), self.seat());
                     ctx.link().send_future(async move {
                         if is_first {
-                            let body = serde_json::to_string(&game.state).unwrap();
-                            let response = match Request::post("/prove/init")
-                                .header("Content-Type", "application/json")
-                                .body(body)
-                                .send()
-                                .await
-                            {
-                                Ok(response) => response,
-                                Err(err) => {
-                                    return GameMsg::Error(format!("POST /prove/init: {}", err));
-                                }
-                            };
-                            let receipt = match response.text().await {
+                            let receipt = match prove("/prove/init", &params).await {
                                 Ok(receipt) => receipt,
-                                Err(err) => {
-                                    return GameMsg::Error(format!("receipt: {}", err));
-                                }
+                                Err(err) => return GameMsg::Error(err),
                             };
                             match contract.join_game(&game.name, &receipt, pos.x, pos.y).await {
                                 Ok(()) => {
@@ -272,7 +317,7 @@ impl Component for GameProvider {
                                     GameMsg::SaveAndWait
                                 }
                                 Err(err) => {
-                                    return GameMsg::Error(format!("join_game: {:?}", err));
+                                    return GameMsg::Error(format!("join_game: {}", err));
                                 }
                             }
                         } else {
@@ -285,7 +330,7 @@ impl Component for GameProvider {
                                     GameMsg::SaveAndWait
                                 }
                                 Err(err) => {
-                                    return GameMsg::Error(format!("turn: {:?}", err));
+                                    return GameMsg::Error(format!("turn: {}", err));
                                 }
                             }
                         }
@@ -297,7 +342,9 @@ impl Component for GameProvider {
                 }
             }
             GameMsg::WaitTurn => {
-                self.game.status = format!("Waiting for other player.");
+                if !self.game.forfeit_claimable {
+                    self.game.status = format!("Waiting for other player.");
+                }
                 self.journal.send("GameMsg::WaitTurn".into());
                 let until = self.game.og_until as u32; //ctx.props().until as u32;
                 let game = self.game.clone();
@@ -306,20 +353,30 @@ impl Component for GameProvider {
                     let contract_state = match contract.get_state(&game.name).await {
                         Ok(state) => state,
                         Err(err) => {
-                            return GameMsg::Error(format!("get_state: {:?}", err));
+                            return GameMsg::Error(format!("get_state: {}", err));
                         }
                     };
-                    if contract_state.next_turn == until {
+                    if let Some(winner) = contract_state.winner {
+                        GameMsg::GameOver(winner)
+                    } else if contract_state.next_turn == until {
                         GameMsg::ProcessTurn(contract_state)
                     } else {
+                        // Keep polling past the deadline, the opponent may
+                        // still move before the forfeit is claimed
+                        let timed_out = contract_state.deadline_passed(js_sys::Date::now() as u64);
                         TimeoutFuture::new(WAIT_TURN_INTERVAL).await;
-                        GameMsg::WaitTurn
+                        if timed_out {
+                            GameMsg::OpponentTimedOut
+                        } else {
+                            GameMsg::WaitTurn
+                        }
                     }
                 });
                 true
             }
             GameMsg::ProcessTurn(contract_state) => {
                 self.game.status = format!("ProcessTurn");
+                self.game.forfeit_claimable = false;
                 self.journal.send("GameMsg::ProcessTurn".into());
                 let state = self.game.state.clone();
                 if let Some(last_shot) = self.game.last_shot.clone() {
@@ -332,43 +389,31 @@ impl Component for GameProvider {
                                 alert("You sunk an opponent's ship!");
                                 HitType::Core(CoreHitType::Sunk(contract_state.sunk_what.unwrap()))
                             }
+                            3 => HitType::Core(CoreHitType::InvalidShot),
                             _ => unreachable!(),
                         },
                     );
                 }
                 let until = self.game.og_until; //ctx.props().until;
+                let seat = self.seat();
                 ctx.link().send_future(async move {
                     let player = if until == 2 {
                         contract_state.p1
                     } else {
                         contract_state.p2
                     };
+                    // A repeated or off-board shot is answered with a proven
+                    // HitType::InvalidShot like any other
                     let shot = Position::new(player.shot_x, player.shot_y);
                     let params = RoundParams {
                         state: state.clone(),
                         shot: shot.clone(),
+                        seat,
                     };
-                    let body = serde_json::to_string(&params).unwrap();
-                    let response = match Request::post("/prove/turn")
-                        .header("Content-Type", "application/json")
-                        .body(body)
-                        .send()
-                        .await
-                    {
-                        Ok(response) => response,
-                        Err(err) => {
-                            return GameMsg::Error(format!("POST /prove/turn: {}", err));
-                        }
-                    };
-                    let result = match response.text().await {
-                        Ok(result) => result,
-                        Err(err) => {
-                            return GameMsg::Error(format!("result: {}", err));
-                        }
-                    };
-                    match serde_json::from_str::<TurnResult>(&result) {
-                        Ok(result) => GameMsg::UpdateState(result.receipt, result.state, shot),
-                        Err(err) => GameMsg::Error(format!("json fail: {}", err)),
+                    // The prover only returns the receipt, the new state is ours to compute
+                    match prove("/prove/turn", &params).await {
+                        Ok(receipt) => GameMsg::UpdateState(receipt, params.process(), shot),
+                        Err(err) => GameMsg::Error(err),
                     }
                 });
                 true
@@ -382,10 +427,12 @@ impl Component for GameProvider {
                     let contract_state = match contract.get_state(&game.name).await {
                         Ok(state) => state,
                         Err(err) => {
-                            return GameMsg::Error(format!("CheckTurn get_state: {:?}", err));
+                            return GameMsg::Error(format!("CheckTurn get_state: {}", err));
                         }
                     };
-                    if contract_state.next_turn == until {
+                    if let Some(winner) = contract_state.winner {
+                        GameMsg::GameOver(winner)
+                    } else if contract_state.next_turn == until {
                         // process the turn if it was not processed yet
                         if !turn_processed {
                             GameMsg::ProcessTurn(contract_state)
@@ -422,6 +469,74 @@ impl Component for GameProvider {
                 self.game.status = format!("Ready!");
                 true
             }
+            GameMsg::GameOver(winner) => {
+                log::info!("GameMsg::GameOver {}, winner: {}", self.game.name, winner);
+                self.journal.send("GameMsg::GameOver".into());
+                self.game.forfeit_claimable = false;
+                self.game.game_over = true;
+                self.game.status = format!("Game over! Winner: {}", winner);
+                LocalStorage::set(self.game.name.clone(), self.game.clone()).unwrap();
+                alert(&self.game.status);
+                true
+            }
+            // Publishing the fleet is up to the player
+            GameMsg::Reveal => {
+                self.journal.send("GameMsg::Reveal".into());
+                self.game.status = format!("Revealing fleet...");
+                let game = self.game.clone();
+                let contract = self.contract.clone();
+                ctx.link().send_future(async move {
+                    let receipt = match prove("/prove/reveal", &game.state).await {
+                        Ok(receipt) => receipt,
+                        Err(err) => return GameMsg::RevealFailed(err),
+                    };
+                    match contract.reveal(&game.name, &receipt).await {
+                        Ok(()) => GameMsg::Revealed,
+                        Err(err) => GameMsg::RevealFailed(err.to_string()),
+                    }
+                });
+                true
+            }
+            GameMsg::Revealed => {
+                log::info!("GameMsg::Revealed {}", self.game.name);
+                self.journal.send("GameMsg::Revealed".into());
+                self.game.revealed = true;
+                self.game.status = format!("Fleet revealed.");
+                LocalStorage::set(self.game.name.clone(), self.game.clone()).unwrap();
+                true
+            }
+            // The chain rejects a fleet that is not the one it holds, for
+            // instance when our last turn never made it on chain
+            GameMsg::RevealFailed(err) => {
+                self.game.status = format!("Reveal rejected: {}", err);
+                alert(&self.game.status);
+                true
+            }
+            GameMsg::OpponentTimedOut => {
+                self.journal.send("GameMsg::OpponentTimedOut".into());
+                self.game.status = format!("Other player timed out.");
+                self.game.forfeit_claimable = true;
+                ctx.link().send_message(GameMsg::WaitTurn);
+                true
+            }
+            GameMsg::ClaimForfeit => {
+                self.journal.send("GameMsg::ClaimForfeit".into());
+                self.game.forfeit_claimable = false;
+                let game = self.game.clone();
+                let contract = self.contract.clone();
+                ctx.link().send_future(async move {
+                    match contract.claim_forfeit(&game.name).await {
+                        Ok(()) => GameMsg::ForfeitClaimed,
+                        Err(err) => GameMsg::Error(format!("claim_forfeit: {}", err)),
+                    }
+                });
+                true
+            }
+            // Still polling, which picks up the game over
+            GameMsg::ForfeitClaimed => {
+                self.game.status = format!("Forfeit claimed.");
+                true
+            }
             GameMsg::Error(msg) => {
                 self.game.status = msg;
                 true
@@ -13,19 +13,26 @@
 // limitations under the License.
 
 use yew::{context::ContextHandle, prelude::*};
+use yew_agent::{Dispatched, Dispatcher};
 
 use crate::{
     board::Board,
-    game::{GameSession, Side},
+    bus::EventBus,
+    game::{GameMsg, GameSession, Side},
 };
 
 pub enum Msg {
     Game(GameSession),
+    ClaimForfeit,
+    Reveal,
 }
 
 pub struct Layout {
+    game_agent: Dispatcher<EventBus<GameMsg>>,
     name: String,
     status: String,
+    forfeit_claimable: bool,
+    revealable: bool,
     _game_handle: ContextHandle<GameSession>,
 }
 
@@ -36,8 +43,11 @@ impl Component for Layout {
     fn create(ctx: &Context<Self>) -> Self {
         let (game, _game_handle) = ctx.link().context(ctx.link().callback(Msg::Game)).unwrap();
         Layout {
+            game_agent: EventBus::dispatcher(),
             name: game.name.clone(),
             status: game.status.clone(),
+            forfeit_claimable: game.forfeit_claimable,
+            revealable: game.game_over && !game.revealed,
             _game_handle,
         }
     }
@@ -46,12 +56,22 @@ impl Component for Layout {
         match msg {
             Msg::Game(game) => {
                 self.status = game.status.clone();
+                self.forfeit_claimable = game.forfeit_claimable;
+                self.revealable = game.game_over && !game.revealed;
                 true
             }
+            Msg::ClaimForfeit => {
+                self.game_agent.send(GameMsg::ClaimForfeit);
+                false
+            }
+            Msg::Reveal => {
+                self.game_agent.send(GameMsg::Reveal);
+                false
+            }
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         html! {
             <>
                 <div class="container">
@@ -69,6 +89,20 @@ impl Component for Layout {
                     <p class="p-2 text-center border">
                         {&self.status}
                     </p>
+                    if self.forfeit_claimable {
+                        <button
+                            class="btn btn-primary"
+                            onclick={ctx.link().callback(|_| Msg::ClaimForfeit)}>
+                            {"Claim forfeit"}
+                        </button>
+                    }
+                    if self.revealable {
+                        <button
+                            class="btn btn-secondary"
+                            onclick={ctx.link().callback(|_| Msg::Reveal)}>
+                            {"Reveal fleet"}
+                        </button>
+                    }
                 </div>
             </>
         }
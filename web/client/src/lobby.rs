@@ -15,17 +15,30 @@
 use std::rc::Rc;
 
 use gloo::storage::{LocalStorage, Storage};
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_agent::{Dispatched, Dispatcher};
 use yew_router::{components::Link, history::History, prelude::RouterScopeExt};
 
-use crate::{bus::EventBus, contract::Contract, near::NearContract, wallet::WalletContext, Route};
+use crate::{
+    bus::EventBus,
+    contract::Contract,
+    prover,
+    wallet::{GameContract, WalletContext},
+    NewGameQuery, Route,
+};
+
+const DEFAULT_TURN_TIMEOUT_SECS: u64 = 60 * 60;
 
 pub struct Lobby {
     journal: Dispatcher<EventBus<String>>,
-    contract: Rc<NearContract>,
+    contract: Rc<GameContract>,
     games: Vec<String>,
+    is_owner: bool,
+    variant: String,
+    opponent: String,
+    turn_timeout_secs: u64,
+    remote_proving: bool,
 }
 
 #[derive(Clone)]
@@ -33,6 +46,11 @@ pub enum Msg {
     ClearGames,
     ListGames(Vec<String>),
     NewGame(String),
+    Owner(String),
+    SetVariant(String),
+    SetOpponent(String),
+    SetTurnTimeout(u64),
+    SetRemoteProving(bool),
 }
 
 impl Component for Lobby {
@@ -47,10 +65,18 @@ impl Component for Lobby {
         let contract = wallet.contract.clone();
         ctx.link()
             .send_future(async move { Msg::ListGames(contract.list_games().await.unwrap()) });
+        let contract = wallet.contract.clone();
+        ctx.link()
+            .send_future(async move { Msg::Owner(contract.owner().await.unwrap()) });
         Lobby {
             journal: EventBus::dispatcher(),
             contract: wallet.contract.clone(),
             games: Vec::new(),
+            is_owner: false,
+            variant: String::from("standard"),
+            opponent: String::new(),
+            turn_timeout_secs: DEFAULT_TURN_TIMEOUT_SECS,
+            remote_proving: prover::remote_proving(),
         }
     }
 
@@ -75,9 +101,44 @@ impl Component for Lobby {
                 });
                 true
             }
+            Msg::Owner(owner) => {
+                let (wallet, _) = ctx
+                    .link()
+                    .context::<WalletContext>(Callback::noop())
+                    .unwrap();
+                self.is_owner = wallet.wallet.current_user().unwrap() == owner;
+                true
+            }
             Msg::NewGame(name) => {
                 self.journal.send("Lobby::NewGame".into());
-                ctx.link().history().unwrap().push(Route::NewGame { name });
+                let variant = self.variant.clone();
+                let opponent = self.opponent.trim();
+                let query = NewGameQuery {
+                    opponent: (!opponent.is_empty()).then(|| opponent.to_string()),
+                    turn_timeout_secs: Some(self.turn_timeout_secs),
+                };
+                ctx.link()
+                    .history()
+                    .unwrap()
+                    .push_with_query(Route::NewGame { variant, name }, query)
+                    .unwrap();
+                true
+            }
+            Msg::SetVariant(variant) => {
+                self.variant = variant;
+                false
+            }
+            Msg::SetOpponent(opponent) => {
+                self.opponent = opponent;
+                false
+            }
+            Msg::SetTurnTimeout(secs) => {
+                self.turn_timeout_secs = secs;
+                false
+            }
+            Msg::SetRemoteProving(enabled) => {
+                prover::set_remote_proving(enabled);
+                self.remote_proving = enabled;
                 true
             }
         }
@@ -94,6 +155,22 @@ impl Component for Lobby {
                 None
             }
         });
+        let onchange = ctx.link().callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            Msg::SetVariant(select.value())
+        });
+        let onopponent = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SetOpponent(input.value())
+        });
+        let ontimeout = ctx.link().callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            Msg::SetTurnTimeout(select.value().parse().unwrap_or(DEFAULT_TURN_TIMEOUT_SECS))
+        });
+        let onremote = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            Msg::SetRemoteProving(input.checked())
+        });
         let games = self.games.clone();
         html! {
             <div>
@@ -115,16 +192,39 @@ impl Component for Lobby {
                     }
                     </ul>
                 }
-                <button
-                    class="btn btn-primary"
-                    onclick={ctx.link().callback(|_|Msg::ClearGames)}>
-                    {"Clear"}
-                </button>
+                if self.is_owner {
+                    <button
+                        class="btn btn-primary"
+                        onclick={ctx.link().callback(|_|Msg::ClearGames)}>
+                        {"Clear"}
+                    </button>
+                }
                 <form>
+                    <select {onchange}>
+                        <option value="standard" selected=true>{"Standard (10x10)"}</option>
+                        <option value="quick">{"Quick (8x8)"}</option>
+                    </select>
+                    <select onchange={ontimeout}>
+                        <option value="300">{"5 minutes per turn"}</option>
+                        <option value="3600" selected=true>{"1 hour per turn"}</option>
+                        <option value="86400">{"1 day per turn"}</option>
+                    </select>
+                    <input
+                        placeholder="Opponent (anyone if empty)"
+                        onchange={onopponent}
+                    />
                     <input
                         placeholder="Enter new game name"
                         {onkeypress}
                     />
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked={self.remote_proving}
+                            onchange={onremote}
+                        />
+                        {" Prove on the server when no local prover is running (the server sees your fleet)"}
+                    </label>
                 </form>
             </div>
         }
@@ -0,0 +1,190 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use battleship_protocol::BattleshipError;
+use gloo::{
+    dialogs::prompt,
+    storage::{LocalStorage, Storage},
+};
+use reqwasm::http::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+
+use crate::contract::{Contract, ContractError, ContractState};
+
+const PLAYER_KEY: &str = "battleship.player";
+
+// Stands in for the NEAR wallet when playing against the local coordinator:
+// signing in just picks a player name.
+#[derive(PartialEq)]
+pub struct LocalWallet;
+
+impl LocalWallet {
+    pub fn new() -> Result<Self, JsValue> {
+        Ok(LocalWallet)
+    }
+
+    pub fn sign_in(&self) -> Result<(), JsValue> {
+        if let Some(name) = prompt("Player name", None) {
+            LocalStorage::set(PLAYER_KEY, name.trim()).unwrap();
+        }
+        Ok(())
+    }
+
+    pub fn sign_out(&self) -> Result<(), JsValue> {
+        LocalStorage::delete(PLAYER_KEY);
+        Ok(())
+    }
+
+    pub fn current_user(&self) -> Result<String, JsValue> {
+        Ok(LocalStorage::get(PLAYER_KEY).unwrap_or_default())
+    }
+
+    pub fn get_contract(&self) -> Result<LocalContract, JsValue> {
+        Ok(LocalContract {
+            player: self.current_user()?,
+        })
+    }
+}
+
+#[derive(PartialEq)]
+pub struct LocalContract {
+    player: String,
+}
+
+fn game_path(name: &str, action: &str) -> String {
+    let name = String::from(js_sys::encode_uri_component(name));
+    format!("/games/{}{}", name, action)
+}
+
+async fn read<T: DeserializeOwned>(response: Response) -> Result<T, ContractError> {
+    if !response.ok() {
+        // The coordinator reports errors like a contract panic
+        let msg = response.text().await.unwrap_or_default();
+        return Err(ContractError::from_message(msg));
+    }
+    response
+        .json()
+        .await
+        .map_err(|err| ContractError::Other(err.to_string()))
+}
+
+impl LocalContract {
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ContractError> {
+        let response = Request::get(path)
+            .send()
+            .await
+            .map_err(|err| ContractError::Other(err.to_string()))?;
+        read(response).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<(), ContractError> {
+        let response = Request::post(path)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|err| ContractError::Other(err.to_string()))?;
+        read::<Value>(response).await.map(|_| ())
+    }
+}
+
+#[async_trait(?Send)]
+impl Contract for LocalContract {
+    async fn get_state(&self, name: &str) -> Result<ContractState, ContractError> {
+        let state: Option<ContractState> = self.get(&game_path(name, "")).await?;
+        state.ok_or(BattleshipError::GameNotFound.into())
+    }
+
+    async fn list_games(&self) -> Result<Vec<String>, ContractError> {
+        self.get("/games").await
+    }
+
+    // Anyone may clear the local coordinator.
+    async fn owner(&self) -> Result<String, ContractError> {
+        Ok(self.player.clone())
+    }
+
+    async fn clear_games(&self) -> Result<(), ContractError> {
+        let response = Request::delete("/games")
+            .send()
+            .await
+            .map_err(|err| ContractError::Other(err.to_string()))?;
+        if !response.ok() {
+            let msg = response.text().await.unwrap_or_default();
+            return Err(ContractError::from_message(msg));
+        }
+        Ok(())
+    }
+
+    async fn new_game(
+        &self,
+        name: &str,
+        receipt: &str,
+        opponent: Option<&str>,
+        turn_timeout_secs: Option<u64>,
+    ) -> Result<(), ContractError> {
+        let body = json!({
+            "player": self.player,
+            "receipt": receipt,
+            "opponent": opponent,
+            "turn_timeout_secs": turn_timeout_secs,
+        });
+        self.post(&game_path(name, ""), body).await
+    }
+
+    async fn join_game(
+        &self,
+        name: &str,
+        receipt: &str,
+        shot_x: u32,
+        shot_y: u32,
+    ) -> Result<(), ContractError> {
+        let body = json!({
+            "player": self.player,
+            "receipt": receipt,
+            "shot_x": shot_x,
+            "shot_y": shot_y,
+        });
+        self.post(&game_path(name, "/join"), body).await
+    }
+
+    async fn turn(
+        &self,
+        name: &str,
+        receipt: &str,
+        shot_x: u32,
+        shot_y: u32,
+    ) -> Result<(), ContractError> {
+        let body = json!({
+            "player": self.player,
+            "receipt": receipt,
+            "shot_x": shot_x,
+            "shot_y": shot_y,
+        });
+        self.post(&game_path(name, "/turn"), body).await
+    }
+
+    async fn reveal(&self, name: &str, receipt: &str) -> Result<(), ContractError> {
+        let body = json!({ "receipt": receipt });
+        self.post(&game_path(name, "/reveal"), body).await
+    }
+
+    async fn claim_forfeit(&self, name: &str) -> Result<(), ContractError> {
+        let body = json!({ "player": self.player });
+        self.post(&game_path(name, "/forfeit"), body).await
+    }
+}
@@ -15,32 +15,41 @@
 mod board;
 mod bus;
 mod contract;
+#[cfg(not(feature = "local"))]
 mod ffi;
 mod game;
 mod journal;
 mod layout;
 mod lobby;
+#[cfg(feature = "local")]
+mod local;
+#[cfg(not(feature = "local"))]
 mod near;
+mod prover;
 mod wallet;
 
 use std::rc::Rc;
 
 use bus::EventBus;
-use near::NearWallet;
+use serde::{Deserialize, Serialize};
 use yew::prelude::*;
 use yew_agent::{Dispatched, Dispatcher};
 use yew_router::prelude::*;
 
 use crate::{
-    game::GameProvider, journal::Journal, layout::Layout, lobby::Lobby, wallet::WalletProvider,
+    game::{variant_rules, GameProvider},
+    journal::Journal,
+    layout::Layout,
+    lobby::Lobby,
+    wallet::{Wallet, WalletProvider},
 };
 
 #[derive(Debug, Clone, PartialEq, Routable)]
 enum Route {
     #[at("/")]
     Lobby,
-    #[at("/new/:name")]
-    NewGame { name: String },
+    #[at("/new/:variant/:name")]
+    NewGame { variant: String, name: String },
     #[at("/join/:name")]
     JoinGame { name: String },
     #[not_found]
@@ -48,6 +57,14 @@ enum Route {
     NotFound,
 }
 
+// The optional settings of a new game, carried in the query string of
+// Route::NewGame.
+#[derive(Default, Deserialize, Serialize)]
+pub struct NewGameQuery {
+    pub opponent: Option<String>,
+    pub turn_timeout_secs: Option<u64>,
+}
+
 enum Msg {
     SignIn,
     SignOut,
@@ -55,7 +72,7 @@ enum Msg {
 
 struct App {
     journal: Dispatcher<EventBus<String>>,
-    wallet: Rc<NearWallet>,
+    wallet: Rc<Wallet>,
 }
 
 impl Component for App {
@@ -63,7 +80,7 @@ impl Component for App {
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
-        let wallet = Rc::new(NearWallet::new().unwrap());
+        let wallet = Rc::new(Wallet::new().unwrap());
         Self {
             journal: EventBus::dispatcher(),
             wallet,
@@ -140,7 +157,7 @@ impl Component for App {
 }
 
 impl App {
-    fn view_main(&self, wallet: &Rc<NearWallet>) -> Html {
+    fn view_main(&self, wallet: &Rc<Wallet>) -> Html {
         let render = Switch::render(move |routes| switch(routes));
         html! {
             <WalletProvider wallet={wallet.clone()}>
@@ -153,11 +170,7 @@ impl App {
 fn switch(routes: &Route) -> Html {
     match routes.clone() {
         Route::Lobby => html! { <Lobby /> },
-        Route::NewGame { name } => html! {
-            <GameProvider {name} until={1}>
-                <Layout />
-            </GameProvider>
-        },
+        Route::NewGame { variant, name } => html! { <NewGame {variant} {name} /> },
         Route::JoinGame { name } => html! {
             <GameProvider {name} until={2}>
                 <Layout />
@@ -167,6 +180,29 @@ fn switch(routes: &Route) -> Html {
     }
 }
 
+#[derive(Properties, PartialEq)]
+struct NewGameProps {
+    variant: String,
+    name: String,
+}
+
+#[function_component(NewGame)]
+fn new_game(props: &NewGameProps) -> Html {
+    let query = use_location()
+        .and_then(|location| location.query::<NewGameQuery>().ok())
+        .unwrap_or_default();
+    html! {
+        <GameProvider
+            name={props.name.clone()}
+            until={1}
+            rules={variant_rules(&props.variant)}
+            opponent={query.opponent}
+            turn_timeout_secs={query.turn_timeout_secs}>
+            <Layout />
+        </GameProvider>
+    }
+}
+
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::start_app::<App>();
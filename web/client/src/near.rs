@@ -15,7 +15,10 @@
 use async_trait::async_trait;
 use wasm_bindgen::JsValue;
 
-use crate::{contract::Contract, contract::ContractState, ffi};
+use crate::{
+    contract::{Contract, ContractError, ContractState},
+    ffi,
+};
 
 pub struct NearWallet {
     wallet: ffi::NearWallet,
@@ -70,22 +73,40 @@ impl PartialEq for NearContract {
 
 #[async_trait(?Send)]
 impl Contract for NearContract {
-    async fn get_state(&self, name: &str) -> Result<ContractState, JsValue> {
+    async fn get_state(&self, name: &str) -> Result<ContractState, ContractError> {
         let value: JsValue = self.contract.game_state(name).await?;
-        Ok(ContractState::from_jsvalue(value))
+        Ok(serde_wasm_bindgen::from_value(value).unwrap())
     }
 
-    async fn list_games(&self) -> Result<Vec<String>, JsValue> {
+    async fn list_games(&self) -> Result<Vec<String>, ContractError> {
         let games = self.contract.list_games().await?;
         Ok(games.into_serde().unwrap())
     }
 
-    async fn clear_games(&self) -> Result<(), JsValue> {
-        self.contract.clear_games().await
+    async fn owner(&self) -> Result<String, ContractError> {
+        let owner = self.contract.owner().await?;
+        Ok(owner.into_serde().unwrap())
     }
 
-    async fn new_game(&self, name: &str, receipt: &str) -> Result<(), JsValue> {
-        self.contract.new_game(name, receipt).await
+    async fn clear_games(&self) -> Result<(), ContractError> {
+        Ok(self.contract.clear_games().await?)
+    }
+
+    async fn new_game(
+        &self,
+        name: &str,
+        receipt: &str,
+        opponent: Option<&str>,
+        turn_timeout_secs: Option<u64>,
+    ) -> Result<(), ContractError> {
+        // A u64 would cross into JS as a BigInt, which JSON cannot carry;
+        // out of range values are left for the contract to reject
+        let turn_timeout_secs =
+            turn_timeout_secs.map(|secs| u32::try_from(secs).unwrap_or(u32::MAX));
+        Ok(self
+            .contract
+            .new_game(name, receipt, opponent, turn_timeout_secs)
+            .await?)
     }
 
     async fn join_game(
@@ -94,8 +115,11 @@ impl Contract for NearContract {
         receipt: &str,
         shot_x: u32,
         shot_y: u32,
-    ) -> Result<(), JsValue> {
-        self.contract.join_game(name, receipt, shot_x, shot_y).await
+    ) -> Result<(), ContractError> {
+        Ok(self
+            .contract
+            .join_game(name, receipt, shot_x, shot_y)
+            .await?)
     }
 
     async fn turn(
@@ -104,7 +128,15 @@ impl Contract for NearContract {
         receipt: &str,
         shot_x: u32,
         shot_y: u32,
-    ) -> Result<(), JsValue> {
-        self.contract.turn(name, receipt, shot_x, shot_y).await
+    ) -> Result<(), ContractError> {
+        Ok(self.contract.turn(name, receipt, shot_x, shot_y).await?)
+    }
+
+    async fn reveal(&self, name: &str, receipt: &str) -> Result<(), ContractError> {
+        Ok(self.contract.reveal(name, receipt).await?)
+    }
+
+    async fn claim_forfeit(&self, name: &str) -> Result<(), ContractError> {
+        Ok(self.contract.claim_forfeit(name).await?)
     }
 }
@@ -0,0 +1,112 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use gloo::{
+    storage::{LocalStorage, Storage},
+    timers::future::TimeoutFuture,
+};
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+
+const JOB_POLL_INTERVAL: u32 = 1_000;
+
+// The battleship-prover sidecar, which keeps the fleet on this machine.
+const LOCAL_PROVER: &str = "http://127.0.0.1:3001";
+
+// Set once the player agrees to send their fleet to the web server instead.
+const REMOTE_PROVING_KEY: &str = "battleship.remote_proving";
+
+#[derive(Deserialize)]
+struct JobTicket {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct InvalidInput {
+    error: String,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+enum JobStatus {
+    Queued,
+    Running,
+    Done { output: String },
+    Failed { error: String },
+}
+
+pub fn remote_proving() -> bool {
+    LocalStorage::get(REMOTE_PROVING_KEY).unwrap_or(false)
+}
+
+pub fn set_remote_proving(enabled: bool) {
+    LocalStorage::set(REMOTE_PROVING_KEY, enabled).unwrap();
+}
+
+// Prefers the local sidecar. Whoever runs the web server can see every fleet
+// it proves, so it is only used if the player opted in.
+async fn prover_url() -> Result<&'static str, String> {
+    let health = format!("{}/health", LOCAL_PROVER);
+    if let Ok(response) = Request::get(&health).send().await {
+        if response.ok() {
+            return Ok(LOCAL_PROVER);
+        }
+    }
+    if remote_proving() {
+        return Ok("");
+    }
+    Err(format!(
+        "No local prover at {}: run battleship-prover, or allow proving on the server",
+        LOCAL_PROVER
+    ))
+}
+
+// Submits a proving job and polls it until it finishes, returning the job
+// output.
+pub async fn prove<T: Serialize>(path: &str, input: &T) -> Result<String, String> {
+    let base = prover_url().await?;
+    let body = serde_json::to_string(input).unwrap();
+    let response = Request::post(&format!("{}{}", base, path))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| format!("POST {}: {}", path, err))?;
+    if response.status() == 422 {
+        let invalid: InvalidInput = response
+            .json()
+            .await
+            .map_err(|err| format!("POST {}: {}", path, err))?;
+        return Err(format!("POST {}: invalid input: {}", path, invalid.error));
+    }
+    let ticket: JobTicket = response
+        .json()
+        .await
+        .map_err(|err| format!("POST {}: {}", path, err))?;
+    let status_path = format!("{}/jobs/{}", base, ticket.id);
+    loop {
+        let status: JobStatus = Request::get(&status_path)
+            .send()
+            .await
+            .map_err(|err| format!("GET {}: {}", status_path, err))?
+            .json()
+            .await
+            .map_err(|err| format!("GET {}: {}", status_path, err))?;
+        match status {
+            JobStatus::Queued | JobStatus::Running => TimeoutFuture::new(JOB_POLL_INTERVAL).await,
+            JobStatus::Done { output } => return Ok(output),
+            JobStatus::Failed { error } => return Err(format!("{}: {}", path, error)),
+        }
+    }
+}
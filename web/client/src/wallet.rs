@@ -16,22 +16,25 @@ use std::rc::Rc;
 
 use yew::prelude::*;
 
-use crate::near::{NearContract, NearWallet};
+#[cfg(feature = "local")]
+pub use crate::local::{LocalContract as GameContract, LocalWallet as Wallet};
+#[cfg(not(feature = "local"))]
+pub use crate::near::{NearContract as GameContract, NearWallet as Wallet};
 
 #[derive(PartialEq, Clone)]
 pub struct WalletContext {
-    wallet: Rc<NearWallet>,
-    pub contract: Rc<NearContract>,
+    pub wallet: Rc<Wallet>,
+    pub contract: Rc<GameContract>,
 }
 
 pub struct WalletProvider {
-    wallet: Rc<NearWallet>,
-    contract: Rc<NearContract>,
+    wallet: Rc<Wallet>,
+    contract: Rc<GameContract>,
 }
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
-    pub wallet: Rc<NearWallet>,
+    pub wallet: Rc<Wallet>,
     #[prop_or_default]
     pub children: Children,
 }
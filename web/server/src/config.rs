@@ -0,0 +1,44 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::IpAddr;
+
+use clap::Parser;
+
+use battleship_prover::ProverConfig;
+
+/// Proving service for the battleship web client.
+#[derive(Parser)]
+#[clap(version)]
+pub struct Config {
+    /// Address to listen on.
+    #[clap(long, env = "BATTLESHIP_ADDR", default_value = "127.0.0.1")]
+    pub addr: IpAddr,
+
+    /// Port to listen on.
+    #[clap(long, env = "BATTLESHIP_PORT", default_value_t = 3000)]
+    pub port: u16,
+
+    /// Tracing filter directives.
+    #[clap(long, env = "RUST_LOG", default_value = "info,server,tower_http=debug")]
+    pub log: String,
+
+    /// Also serve an in-memory game coordinator in place of the NEAR contract.
+    /// It trusts callers to name themselves, so keep `addr` on loopback.
+    #[clap(long, env = "BATTLESHIP_COORDINATOR")]
+    pub coordinator: bool,
+
+    #[clap(flatten)]
+    pub prover: ProverConfig,
+}
@@ -0,0 +1,454 @@
+// Copyright 2022 Risc0, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An in-memory stand-in for `BattleshipContract`, so that games can be
+//! played against localhost without a NEAR network. Players are identified
+//! by the name they send with each request, so anyone who can reach the
+//! coordinator can move for any player or clear every game: it is a
+//! single-user development tool, not a service to expose to other players.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use battleship_core::{InitCommit, Position, RevealCommit, RoundCommit};
+use battleship_methods::{INIT_ID, REVEAL_ID, TURN_ID};
+use battleship_protocol::{decode_receipt, require, BattleshipError, GameState, Referee};
+
+// Carries the contract's error codes over HTTP, so clients handle both alike.
+#[derive(Debug)]
+pub struct ApiError(pub BattleshipError);
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self.0 {
+            BattleshipError::GameNotFound => StatusCode::NOT_FOUND,
+            BattleshipError::GameExists
+            | BattleshipError::WrongTurn
+            | BattleshipError::DeadlineNotPassed => StatusCode::CONFLICT,
+            BattleshipError::NotYourTurn
+            | BattleshipError::NotAllowed
+            | BattleshipError::SelfJoin
+            | BattleshipError::NotInvited => StatusCode::FORBIDDEN,
+            BattleshipError::BadReceipt
+            | BattleshipError::StateMismatch
+            | BattleshipError::ShotMismatch
+            | BattleshipError::RulesMismatch
+            | BattleshipError::UnsupportedReceipt
+            | BattleshipError::WrongMethod
+            | BattleshipError::ShotOutOfBounds
+            | BattleshipError::DuplicateShot
+            | BattleshipError::WrongSeat
+            | BattleshipError::InvalidTimeout => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+// Errors read the same as a contract panic: "ERR_CODE: message".
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), format!("{}: {}", self.0.code(), self.0)).into_response()
+    }
+}
+
+type Result<T> = std::result::Result<T, BattleshipError>;
+
+fn referee() -> Referee {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    Referee::new(now.as_millis() as u64)
+}
+
+pub struct Coordinator {
+    games: Mutex<HashMap<String, GameState>>,
+    // Off in dev mode, where receipts carry no seal
+    verify_seals: bool,
+}
+
+impl Coordinator {
+    pub fn new(verify_seals: bool) -> Self {
+        Coordinator {
+            games: Mutex::new(HashMap::new()),
+            verify_seals,
+        }
+    }
+
+    fn verify<T: DeserializeOwned>(&self, receipt: &str, method_id: &[u8]) -> Result<T> {
+        decode_receipt(receipt, method_id, self.verify_seals).map_err(|err| {
+            tracing::warn!("rejected receipt: {}", err);
+            err.into()
+        })
+    }
+
+    fn update(
+        &self,
+        name: &str,
+        f: impl FnOnce(&mut GameState) -> Result<()>,
+    ) -> Result<GameState> {
+        let mut games = self.games.lock().unwrap();
+        let state = games.get_mut(name).ok_or(BattleshipError::GameNotFound)?;
+        f(state)?;
+        Ok(state.clone())
+    }
+
+    pub fn list_games(&self) -> Vec<String> {
+        self.games.lock().unwrap().keys().cloned().collect()
+    }
+
+    pub fn game_state(&self, name: &str) -> Option<GameState> {
+        self.games.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn clear_games(&self) {
+        self.games.lock().unwrap().clear();
+    }
+
+    pub fn new_game(
+        &self,
+        name: &str,
+        player: &str,
+        receipt: &str,
+        opponent: Option<String>,
+        turn_timeout_secs: Option<u64>,
+    ) -> Result<GameState> {
+        let commit: InitCommit = self.verify(receipt, INIT_ID)?;
+        let mut games = self.games.lock().unwrap();
+        require(!games.contains_key(name), BattleshipError::GameExists)?;
+        let state = referee().new_game(name, player, commit, opponent, turn_timeout_secs)?;
+        games.insert(name.to_string(), state.clone());
+        Ok(state)
+    }
+
+    pub fn join_game(
+        &self,
+        name: &str,
+        player: &str,
+        receipt: &str,
+        shot_x: u32,
+        shot_y: u32,
+    ) -> Result<GameState> {
+        let commit: InitCommit = self.verify(receipt, INIT_ID)?;
+        let shot = Position::new(shot_x, shot_y);
+        self.update(name, |state| {
+            referee().join_game(state, name, player, commit, shot)
+        })
+    }
+
+    pub fn turn(
+        &self,
+        name: &str,
+        player: &str,
+        receipt: &str,
+        shot_x: u32,
+        shot_y: u32,
+    ) -> Result<GameState> {
+        let commit: RoundCommit = self.verify(receipt, TURN_ID)?;
+        let shot = Position::new(shot_x, shot_y);
+        self.update(name, |state| {
+            referee().turn(state, name, player, commit, shot)
+        })
+    }
+
+    pub fn claim_forfeit(&self, name: &str, player: &str) -> Result<GameState> {
+        self.update(name, |state| referee().claim_forfeit(state, player))
+    }
+
+    pub fn reveal(&self, name: &str, receipt: &str) -> Result<GameState> {
+        let commit: RevealCommit = self.verify(receipt, REVEAL_ID)?;
+        self.update(name, |state| referee().reveal(state, commit))
+    }
+}
+
+#[derive(Deserialize)]
+struct NewGame {
+    player: String,
+    receipt: String,
+    opponent: Option<String>,
+    turn_timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Move {
+    player: String,
+    receipt: String,
+    shot_x: u32,
+    shot_y: u32,
+}
+
+#[derive(Deserialize)]
+struct Reveal {
+    receipt: String,
+}
+
+#[derive(Deserialize)]
+struct Forfeit {
+    player: String,
+}
+
+type Shared = Arc<Coordinator>;
+type Reply = std::result::Result<Json<GameState>, ApiError>;
+
+pub fn routes(coordinator: Coordinator) -> Router {
+    Router::new()
+        .route("/games", get(list_games).delete(clear_games))
+        .route("/games/:name", get(game_state).post(new_game))
+        .route("/games/:name/join", post(join_game))
+        .route("/games/:name/turn", post(turn))
+        .route("/games/:name/reveal", post(reveal))
+        .route("/games/:name/forfeit", post(claim_forfeit))
+        .layer(Extension(Arc::new(coordinator)))
+}
+
+async fn list_games(Extension(games): Extension<Shared>) -> Json<Vec<String>> {
+    Json(games.list_games())
+}
+
+async fn clear_games(Extension(games): Extension<Shared>) -> StatusCode {
+    games.clear_games();
+    StatusCode::NO_CONTENT
+}
+
+async fn game_state(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+) -> Json<Option<GameState>> {
+    Json(games.game_state(&name))
+}
+
+async fn new_game(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+    Json(req): Json<NewGame>,
+) -> Reply {
+    games
+        .new_game(
+            &name,
+            &req.player,
+            &req.receipt,
+            req.opponent,
+            req.turn_timeout_secs,
+        )
+        .map(Json)
+        .map_err(ApiError)
+}
+
+async fn join_game(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+    Json(req): Json<Move>,
+) -> Reply {
+    games
+        .join_game(&name, &req.player, &req.receipt, req.shot_x, req.shot_y)
+        .map(Json)
+        .map_err(ApiError)
+}
+
+async fn turn(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+    Json(req): Json<Move>,
+) -> Reply {
+    games
+        .turn(&name, &req.player, &req.receipt, req.shot_x, req.shot_y)
+        .map(Json)
+        .map_err(ApiError)
+}
+
+async fn reveal(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+    Json(req): Json<Reveal>,
+) -> Reply {
+    games
+        .reveal(&name, &req.receipt)
+        .map(Json)
+        .map_err(ApiError)
+}
+
+async fn claim_forfeit(
+    Extension(games): Extension<Shared>,
+    Path(name): Path<String>,
+    Json(req): Json<Forfeit>,
+) -> Reply {
+    games
+        .claim_forfeit(&name, &req.player)
+        .map(Json)
+        .map_err(ApiError)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request},
+    };
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use battleship_core::{
+        GameRules, GameState as Board, InitParams, RoundParams, Seat, Ship, ShipDirection,
+    };
+    use battleship_prover::{DevProver, ProverBackend};
+
+    use super::*;
+
+    const GAME: &str = "test";
+
+    // One ship per row, each against the left edge
+    fn board(salt: u32) -> Board {
+        let rules = GameRules::default();
+        let ships = (0..rules.spans.len() as u32)
+            .map(|y| Ship::new(0, y, ShipDirection::Horizontal))
+            .collect();
+        Board::new(rules, ships, [salt; 8])
+    }
+
+    fn init(player: &str, salt: u32) -> String {
+        let params = InitParams::new(board(salt), Seat::new(GAME, player));
+        DevProver.prove_init(&params).unwrap()
+    }
+
+    async fn post(app: &Router, uri: &str, body: Value) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn next_turn(body: &str) -> u64 {
+        let state: Value = serde_json::from_str(body).unwrap();
+        state["next_turn"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn play() {
+        let app = routes(Coordinator::new(false));
+        let game = format!("/games/{}", GAME);
+
+        let new_game = json!({"player": "alice", "receipt": init("alice", 1)});
+        let (status, body) = post(&app, &game, new_game.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(next_turn(&body), 0);
+        let (status, body) = post(&app, &game, new_game).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.starts_with("ERR_GAME_EXISTS: "), "{}", body);
+
+        // Bob's board cannot be played from Alice's seat
+        let join = |player: &str, receipt: String| json!({"player": player, "receipt": receipt, "shot_x": 0, "shot_y": 0});
+        let uri = format!("{}/join", game);
+        let (status, body) = post(&app, &uri, join("alice", init("bob", 2))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.starts_with("ERR_SELF_JOIN: "), "{}", body);
+        let (status, body) = post(&app, &uri, join("carol", init("bob", 2))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("ERR_WRONG_SEAT: "), "{}", body);
+        let (status, body) = post(&app, &uri, join("bob", init("bob", 2))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(next_turn(&body), 1);
+
+        let turn = |player: &str| {
+            let params = RoundParams::new(board(1), 0, 0, Seat::new(GAME, player));
+            let receipt = DevProver.prove_turn(&params).unwrap().receipt;
+            json!({"player": player, "receipt": receipt, "shot_x": 5, "shot_y": 5})
+        };
+        let uri = format!("{}/turn", game);
+        let (status, body) = post(&app, &uri, turn("bob")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.starts_with("ERR_NOT_YOUR_TURN: "), "{}", body);
+        let (status, body) = post(&app, &uri, turn("alice")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(next_turn(&body), 2);
+
+        let uri = format!("{}/forfeit", game);
+        let (status, body) = post(&app, &uri, json!({"player": "bob"})).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.starts_with("ERR_NOT_ALLOWED: "), "{}", body);
+        let (status, body) = post(&app, &uri, json!({"player": "alice"})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.starts_with("ERR_DEADLINE_NOT_PASSED: "), "{}", body);
+
+        let (status, body) = post(&app, "/games/missing/forfeit", json!({"player": "alice"})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.starts_with("ERR_GAME_NOT_FOUND: "), "{}", body);
+    }
+
+    #[test]
+    fn statuses() {
+        for (err, status) in [
+            (BattleshipError::GameNotFound, StatusCode::NOT_FOUND),
+            (BattleshipError::GameExists, StatusCode::CONFLICT),
+            (BattleshipError::WrongTurn, StatusCode::CONFLICT),
+            (BattleshipError::DeadlineNotPassed, StatusCode::CONFLICT),
+            (BattleshipError::NotYourTurn, StatusCode::FORBIDDEN),
+            (BattleshipError::NotAllowed, StatusCode::FORBIDDEN),
+            (BattleshipError::SelfJoin, StatusCode::FORBIDDEN),
+            (BattleshipError::NotInvited, StatusCode::FORBIDDEN),
+            (
+                BattleshipError::BadReceipt,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::StateMismatch,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::ShotMismatch,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::RulesMismatch,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::UnsupportedReceipt,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::WrongMethod,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::ShotOutOfBounds,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                BattleshipError::DuplicateShot,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (BattleshipError::WrongSeat, StatusCode::UNPROCESSABLE_ENTITY),
+            (
+                BattleshipError::InvalidTimeout,
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+        ] {
+            assert_eq!(ApiError(err).status(), status, "{}", err.code());
+        }
+    }
+}
@@ -12,36 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::net::{Ipv4Addr, SocketAddr};
+mod config;
+mod coordinator;
 
-use axum::{http::StatusCode, response::IntoResponse, routing::post, Json, Router};
-use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+use axum::{http::StatusCode, response::IntoResponse, routing::post, Json};
+use clap::Parser;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::prelude::*;
 
-use battleship_core::{GameState, RoundParams, RoundResult};
-use battleship_methods::{INIT_ID, INIT_PATH, TURN_ID, TURN_PATH};
-use risc0_zkvm_host::Prover;
-
-#[derive(Deserialize, Serialize)]
-pub struct Receipt {
-    journal: Vec<u8>,
-    seal: Vec<u32>,
-}
-
-#[derive(Deserialize, Serialize)]
-pub struct TurnResult {
-    state: RoundResult,
-    receipt: String,
-}
+use battleship_core::{InitCommit, RoundCommit};
+use battleship_methods::{INIT_ID, TURN_ID};
+use battleship_protocol::{verify_receipt, ReceiptError, VerifyError};
+use battleship_prover::{routes, shutdown_signal};
+use config::Config;
+use coordinator::Coordinator;
 
 #[tokio::main]
 async fn main() {
+    let config = Config::parse();
+
     tracing_subscriber::registry()
         // Filter spans based on the RUST_LOG env var.
-        .with(tracing_subscriber::EnvFilter::new(
-            "info,server,tower_http=debug",
-        ))
+        .with(tracing_subscriber::EnvFilter::new(&config.log))
         // Send a copy of all spans to stdout as JSON.
         .with(
             tracing_subscriber::fmt::layer()
@@ -53,71 +47,51 @@ async fn main() {
         .try_init()
         .unwrap();
 
-    let app = Router::new()
-        .route("/prove/init", post(prove_init))
-        .route("/prove/turn", post(prove_turn))
-        .layer(TraceLayer::new_for_http());
+    let mut app = routes(&config.prover)
+        .route("/verify/init", post(verify_init))
+        .route("/verify/turn", post(verify_turn));
+    if config.coordinator {
+        if !config.addr.is_loopback() {
+            tracing::warn!(
+                "serving the coordinator on {}, anyone who can reach it can play as anyone",
+                config.addr
+            );
+        }
+        tracing::info!("serving the local game coordinator");
+        let games = Coordinator::new(!config.prover.dev_mode);
+        app = app.merge(coordinator::routes(games));
+    }
+    let app = app.layer(TraceLayer::new_for_http());
 
-    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 3000));
+    let addr = SocketAddr::from((config.addr, config.port));
     tracing::info!("listening on {}", addr);
-    let server = axum::Server::bind(&addr).serve(app.into_make_service());
+    let server = axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal());
 
     server.await.unwrap();
 }
 
-fn do_init_proof(name: &str, input: GameState) -> Result<String, risc0_zkvm_host::Exception> {
-    let elf_contents = std::fs::read(name).unwrap();
-    let mut prover = Prover::new(&elf_contents, INIT_ID)?;
-    let vec = risc0_zkvm_serde::to_vec(&input).unwrap();
-    prover.add_input(vec.as_slice())?;
-    let receipt = prover.run()?;
-    let receipt = Receipt {
-        journal: receipt.get_journal().unwrap().to_vec(),
-        seal: receipt.get_seal().unwrap().to_vec(),
-    };
-    Ok(base64::encode(bincode::serialize(&receipt).unwrap()))
-}
-
-fn do_turn_proof(name: &str, input: RoundParams) -> Result<TurnResult, risc0_zkvm_host::Exception> {
-    let elf_contents = std::fs::read(name).unwrap();
-    let mut prover = Prover::new(&elf_contents, TURN_ID)?;
-    let vec = risc0_zkvm_serde::to_vec(&input).unwrap();
-    prover.add_input(vec.as_slice())?;
-    let receipt = prover.run()?;
-    let receipt = Receipt {
-        journal: receipt.get_journal().unwrap().to_vec(),
-        seal: receipt.get_seal().unwrap().to_vec(),
+fn verify_response(err: VerifyError) -> (StatusCode, String) {
+    let status = match err {
+        VerifyError::Envelope(ReceiptError::Malformed) | VerifyError::Malformed => {
+            StatusCode::BAD_REQUEST
+        }
+        VerifyError::Envelope(_) | VerifyError::Invalid | VerifyError::Journal => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
     };
-    let vec = prover.get_output_vec()?;
-    let result = risc0_zkvm_serde::from_slice::<RoundResult>(vec.as_slice()).unwrap();
-    Ok(TurnResult {
-        state: result,
-        receipt: base64::encode(bincode::serialize(&receipt).unwrap()),
-    })
+    (status, err.to_string())
 }
 
-async fn prove_init(Json(payload): Json<GameState>) -> impl IntoResponse {
-    let out = match do_init_proof(INIT_PATH, payload) {
-        Ok(receipt) => receipt,
-        Err(_e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("bad proof load"),
-            )
-        }
-    };
-    (StatusCode::OK, out)
+async fn verify_init(receipt: String) -> impl IntoResponse {
+    verify_receipt::<InitCommit>(&receipt, INIT_ID)
+        .map(Json)
+        .map_err(verify_response)
 }
 
-async fn prove_turn(Json(payload): Json<RoundParams>) -> impl IntoResponse {
-    let out = match do_turn_proof(TURN_PATH, payload) {
-        Ok(receipt) => receipt,
-        Err(_e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("bad proof load"),
-            )
-        }
-    };
-    (StatusCode::OK, serde_json::to_string(&out).unwrap())
+async fn verify_turn(receipt: String) -> impl IntoResponse {
+    verify_receipt::<RoundCommit>(&receipt, TURN_ID)
+        .map(Json)
+        .map_err(verify_response)
 }